@@ -1,15 +1,29 @@
 mod add;
 mod append;
+mod archive;
 mod delete;
 mod done;
+mod history;
 mod list;
 mod priority;
 mod replace;
+mod report;
+mod search;
+mod select;
+mod stats;
+mod syntax;
 
 pub(crate) use add::*;
 pub(crate) use append::*;
+pub(crate) use archive::*;
 pub(crate) use delete::*;
 pub(crate) use done::*;
+pub(crate) use history::*;
 pub(crate) use list::*;
 pub(crate) use priority::*;
 pub(crate) use replace::*;
+pub(crate) use report::*;
+pub(crate) use search::*;
+pub(crate) use select::*;
+pub(crate) use stats::*;
+pub(crate) use syntax::*;
@@ -18,6 +18,9 @@ pub(crate) struct AddArgs {
     /// Task contexts. If task string contains context, it will be added to the task.
     #[clap(long, value_delimiter(','))]
     context: Option<Vec<String>>,
+    /// Assign a stable `id:` tag to the task, so it can be referred to by uid.
+    #[clap(long, default_value = "false")]
+    uid: bool,
 }
 
 #[tracing::instrument(parent = None, skip(manager))]
@@ -29,8 +32,12 @@ pub(crate) async fn cmd_add(manager: &TodoManager, options: AddArgs) -> Result<(
         Ok(task) => task,
         Err(e) => {
             tracing::error!("Failed to parse task: {}", e);
-            if let TodoTxtRsError::Syntax = e {
-                eprintln!("Invalid task syntax: {}", destenation)
+            if let TodoTxtRsError::Syntax(syntax) = &e {
+                eprintln!(
+                    "Invalid task syntax: {}\n{}",
+                    syntax,
+                    super::render_syntax_error(&destenation, syntax)
+                )
             }
             return Err(e.into());
         }
@@ -51,7 +58,11 @@ pub(crate) async fn cmd_add(manager: &TodoManager, options: AddArgs) -> Result<(
         task.description.context.extend_from_slice(contexts);
     }
     tracing::info!("Adding task: {:?}", task);
-    let added = manager.add_async(task).await?;
+    let added = if options.uid {
+        manager.clone().with_auto_id(true).add_async(task).await?
+    } else {
+        manager.add_async(task).await?
+    };
     tracing::info!("Task added: {:?}", added);
     Ok(())
 }
@@ -1,11 +1,11 @@
 use anyhow::Result;
-use todo_txt_manager::TodoManager;
+use todo_txt_manager::{TaskRef, TodoManager};
 use todo_txt_model::prelude::*;
 
-#[derive(Debug, Default, clap::Parser)]
+#[derive(Debug, clap::Parser)]
 pub(crate) struct AppendArgs {
-    /// Task id to append to.
-    id: usize,
+    /// Task id or uid (the value of its `id:` tag) to append to.
+    id: TaskRef,
     /// Task string to append.
     destenations: Vec<String>,
     /// Task Projects. If task string contains project, it will be added to the task.
@@ -26,8 +26,12 @@ pub(crate) async fn cmd_append(manager: &TodoManager, options: AppendArgs) -> Re
         Ok(task) => task,
         Err(e) => {
             tracing::error!("Failed to parse task: {}", e);
-            if let TodoTxtRsError::Syntax = e {
-                eprintln!("Invalid task syntax: {}", destenation)
+            if let TodoTxtRsError::Syntax(syntax) = &e {
+                eprintln!(
+                    "Invalid task syntax: {}\n{}",
+                    syntax,
+                    super::render_syntax_error(&destenation, syntax)
+                )
             }
             return Err(e.into());
         }
@@ -40,7 +44,9 @@ pub(crate) async fn cmd_append(manager: &TodoManager, options: AppendArgs) -> Re
         task.description.context.extend_from_slice(contexts);
     }
     tracing::info!("Appending task at: {}, {:?}", options.id, task);
-    let (id, appended) = manager.append_async(options.id, task.description).await?;
+    let (id, appended) = manager
+        .append_async(options.id.clone(), task.description)
+        .await?;
     tracing::info!("Task appended: {}, {:?}", id, appended);
     Ok(())
 }
@@ -0,0 +1,21 @@
+use anyhow::Result;
+use todo_txt_manager::{ArchiveOptions, TodoManager};
+
+#[derive(Debug, Default, clap::Parser)]
+pub(crate) struct ArchiveArgs {
+    /// Archive only tasks completed at least this many days ago.
+    #[clap(long)]
+    older_than: Option<u32>,
+}
+
+#[tracing::instrument(parent = None, skip(manager))]
+pub(crate) async fn cmd_archive(manager: &TodoManager, options: ArchiveArgs) -> Result<()> {
+    let options = ArchiveOptions {
+        older_than: options.older_than,
+    };
+    tracing::info!("Archiving tasks with options: {:?}", options);
+    let archived = manager.archive_async(options).await?;
+    tracing::info!("Archived tasks: {:?}", archived);
+    println!("Archived {} task(s)", archived.len());
+    Ok(())
+}
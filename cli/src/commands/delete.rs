@@ -1,26 +1,33 @@
 use anyhow::Result;
-use todo_txt_manager::TodoManager;
+use todo_txt_manager::{TaskSelector, TodoManager};
 use todo_txt_model::prelude::TodoTxtRsError;
 
-#[derive(Debug, Default, clap::Parser)]
+#[derive(Debug, clap::Parser)]
 pub(crate) struct DeleteArgs {
-    /// Task ID to delete
-    id: usize,
+    /// Task ids, uids (the value of the `id:` tag) or ranges of ids like `3-7` to delete
+    ids: Vec<TaskSelector>,
+    #[clap(flatten)]
+    select: super::SelectArgs,
 }
 
 #[tracing::instrument(parent = None, skip(manager))]
 pub(crate) async fn cmd_delete(manager: &TodoManager, options: DeleteArgs) -> Result<()> {
-    tracing::info!("Deleting task at: {}", options.id);
-    match manager.delete_async(options.id).await {
+    let ids = super::format_ids(&options.ids);
+    tracing::info!("Deleting task at: {}", ids);
+    let selection = options.select.selection(options.ids)?;
+    match manager.delete_many_async(selection).await {
         Ok(deleted) => {
-            tracing::info!("Deleted task: {:?}", deleted);
+            tracing::info!("Deleted tasks: {:?}", deleted);
+            if deleted.is_empty() {
+                eprintln!("No matching tasks");
+            }
             Ok(())
         }
         Err(e) => {
             tracing::error!("Error: {}", e);
             match &e {
-                TodoTxtRsError::InvalidIndex => {
-                    eprintln!("Invalid index: {}", options.id);
+                TodoTxtRsError::InvalidIndex | TodoTxtRsError::NotFound => {
+                    eprintln!("Invalid index: {}", ids);
                 }
                 e => {
                     eprintln!("Error: {}", e);
@@ -1,11 +1,13 @@
 use anyhow::Result;
-use todo_txt_manager::TodoManager;
+use todo_txt_manager::{TaskSelector, TodoManager};
 use todo_txt_model::prelude::*;
 
-#[derive(Debug, Default, clap::Parser)]
+#[derive(Debug, clap::Parser)]
 pub(crate) struct DoneArgs {
-    /// Task id to mark as done.
-    id: usize,
+    /// Task ids, uids (the value of the `id:` tag) or ranges of ids like `3-7` to update.
+    ids: Vec<TaskSelector>,
+    #[clap(flatten)]
+    select: super::SelectArgs,
 }
 
 #[tracing::instrument(parent = None, skip(manager))]
@@ -14,17 +16,22 @@ pub(crate) async fn cmd_update_state(
     options: DoneArgs,
     done: bool,
 ) -> Result<()> {
-    tracing::info!("Update Task State at: {} {}", options.id, done);
-    match manager.update_state_async(options.id, done).await {
-        Ok((id, task)) => {
-            tracing::info!("Task {} updated: {:?}", id, task);
+    let ids = super::format_ids(&options.ids);
+    tracing::info!("Update Task State at: {} {}", ids, done);
+    let selection = options.select.selection(options.ids)?;
+    match manager.update_state_many_async(selection, done).await {
+        Ok(updated) => {
+            tracing::info!("Tasks updated: {:?}", updated);
+            if updated.is_empty() {
+                eprintln!("No tasks to update");
+            }
             Ok(())
         }
         Err(e) => {
             tracing::error!("Error: {}", e);
             match &e {
-                TodoTxtRsError::InvalidIndex => {
-                    eprintln!("Invalid index: {}", options.id);
+                TodoTxtRsError::InvalidIndex | TodoTxtRsError::NotFound => {
+                    eprintln!("Invalid index: {}", ids);
                 }
                 e => {
                     eprintln!("Error: {}", e);
@@ -0,0 +1,49 @@
+use anyhow::Result;
+use todo_txt_manager::TodoManager;
+use todo_txt_model::prelude::TodoTxtRsError;
+
+#[tracing::instrument(parent = None, skip(manager))]
+pub(crate) async fn cmd_undo(manager: &TodoManager) -> Result<()> {
+    match manager.undo_async().await {
+        Ok(operation) => {
+            tracing::info!("Undone: {}", operation);
+            println!("Undone: {}", operation);
+            Ok(())
+        }
+        Err(e) => {
+            tracing::error!("Error: {}", e);
+            print_history_error(&e);
+            Err(e.into())
+        }
+    }
+}
+
+#[tracing::instrument(parent = None, skip(manager))]
+pub(crate) async fn cmd_redo(manager: &TodoManager) -> Result<()> {
+    match manager.redo_async().await {
+        Ok(operation) => {
+            tracing::info!("Redone: {}", operation);
+            println!("Redone: {}", operation);
+            Ok(())
+        }
+        Err(e) => {
+            tracing::error!("Error: {}", e);
+            print_history_error(&e);
+            Err(e.into())
+        }
+    }
+}
+
+fn print_history_error(e: &TodoTxtRsError) {
+    match e {
+        TodoTxtRsError::NothingToUndo => eprintln!("Nothing to undo"),
+        TodoTxtRsError::NothingToRedo => eprintln!("Nothing to redo"),
+        TodoTxtRsError::HistoryConflict(file) => {
+            eprintln!(
+                "{} was edited since the operation, refusing to overwrite it",
+                file
+            )
+        }
+        e => eprintln!("Error: {}", e),
+    }
+}
@@ -1,27 +1,44 @@
 use anyhow::Result;
 use todo_txt_manager::*;
-use todo_txt_model::{TaskPriority, TaskState};
+use todo_txt_model::prelude::*;
 
 #[derive(Debug, Default, clap::Parser)]
 pub(crate) struct ListArgs {
     /// Filter by task status
     #[clap(short, long)]
     state: Option<TaskState>,
-    /// Filter by task priority.
+    /// Filter by task priority: `A`, a range `A..C`, `any` or `none`.
     #[clap(short, long, value_delimiter(','))]
-    priority: Option<Vec<TaskPriority>>,
+    priority: Option<Vec<PriorityFilter>>,
     /// Filter by project. When multiple projects are provided, the task must contain all of them.
     #[clap(long, value_delimiter(','))]
     project: Option<Vec<String>>,
     /// Filter by context. When multiple contexts are provided, the task must contain all of them.
     #[clap(long, value_delimiter(','))]
     context: Option<Vec<String>>,
-    /// Task order options. The option on the left has the highest priority.
+    /// Filter by urgency computed from due and threshold dates.
     #[clap(short, long, value_delimiter(','))]
-    order: Option<Vec<OrderOption>>,
+    urgency: Option<Vec<TaskUrgency>>,
+    /// Show only overdue tasks. Shorthand for `--urgency overdue`.
+    #[clap(long, conflicts_with = "urgency", default_value = "false")]
+    overdue: bool,
+    /// Hide tasks whose threshold date is in the future.
+    #[clap(long, default_value = "false")]
+    hide_future: bool,
+    /// Filter by a query, e.g. `(+work or +ops) and not @waiting and pri>=B and due<today+3d`.
+    #[clap(short, long)]
+    query: Option<Query>,
+    /// Sort keys as `key[:asc|:desc][:nulls_first|:nulls_last]`, e.g. `priority,due_date:desc`.
+    /// The key on the left has the highest priority.
+    #[clap(short, long, value_delimiter(','))]
+    order: Option<Vec<SortKey>>,
     /// Reverse order of tasks
     #[clap(short, long, requires = "order", default_value = "false")]
     reverse: bool,
+    /// List the tasks of every list in the data directory, prefixed by the name of their list.
+    /// A list is a `<name>.txt` with its `<name>.done.txt`.
+    #[clap(short = 'L', long, default_value = "false")]
+    all_lists: bool,
 }
 
 #[tracing::instrument(parent = None, skip(manager))]
@@ -31,6 +48,14 @@ pub(crate) async fn cmd_list(manager: &TodoManager, options: ListArgs) -> Result
         priority: options.priority,
         project: options.project,
         context: options.context,
+        urgency: if options.overdue {
+            Some(vec![TaskUrgency::Overdue])
+        } else {
+            options.urgency
+        },
+        hide_future: options.hide_future,
+        query: options.query,
+        text: None,
     };
     let order = Order {
         by: options.order,
@@ -42,10 +67,45 @@ pub(crate) async fn cmd_list(manager: &TodoManager, options: ListArgs) -> Result
         filter,
         order
     );
-    let tasks = manager.list_async(filter, order).await?;
-    tracing::info!("Listed {} tasks", tasks.len());
-    for (idx, task) in tasks {
-        println!("{} {}", idx, todo_txt_serializer::to_string(&task));
+    if !options.all_lists {
+        let (tasks, diagnostics) = manager.list_with_diagnostics_async(filter, order).await?;
+        tracing::info!("Listed {} tasks", tasks.len());
+        print_diagnostics(diagnostics);
+        for (idx, task) in tasks {
+            println!("{} {}", idx, todo_txt_serializer::to_string(&task));
+        }
+        return Ok(());
+    }
+    let (tasks, diagnostics) = manager
+        .list_all_with_diagnostics_async(filter, order)
+        .await?;
+    tracing::info!("Listed {} tasks of every list", tasks.len());
+    print_diagnostics(diagnostics);
+    for (name, idx, task) in tasks {
+        println!("{}:{} {}", name, idx, todo_txt_serializer::to_string(&task));
+    }
+    Ok(())
+}
+
+fn print_diagnostics(diagnostics: Vec<Diagnostic>) {
+    for d in diagnostics {
+        tracing::warn!("skipped line: {:?}", d);
+        eprintln!("{}:{}: {}", d.file.display(), d.line, d.error);
+        match &d.error {
+            TodoTxtRsError::Syntax(syntax) => {
+                eprintln!("{}", super::render_syntax_error(&d.raw, syntax))
+            }
+            _ => eprintln!("{}", d.raw),
+        }
+    }
+}
+
+/// Print the lists of the data directory, the active one marked with `*`.
+#[tracing::instrument(parent = None, skip(manager))]
+pub(crate) async fn cmd_lists(manager: &TodoManager) -> Result<()> {
+    for name in manager.lists_async().await? {
+        let marker = if name == manager.get_list() { "*" } else { " " };
+        println!("{} {}", marker, name);
     }
     Ok(())
 }
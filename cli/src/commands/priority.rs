@@ -1,37 +1,67 @@
 use anyhow::Result;
-use todo_txt_manager::TodoManager;
+use todo_txt_manager::{TaskSelector, TodoManager};
 use todo_txt_model::{prelude::TodoTxtRsError, TaskPriority};
 
-#[derive(Debug, Default, clap::Parser)]
+#[derive(Debug, clap::Parser)]
 pub(crate) struct PriorityArgs {
-    /// Task ID to set priority
-    id: usize,
-    /// Priority to set.
-    #[clap(group = "set_priority", default_value = "A")]
-    priority: TaskPriority,
+    /// Task ids, uids (the value of the `id:` tag) or ranges of ids like `3-7`,
+    /// followed by the priority to set. The priority defaults to `A`.
+    #[clap(value_name = "ID... [PRIORITY]")]
+    args: Vec<String>,
     /// Delete priority, instead of setting it
-    #[clap(short, long, group = "set_priority", default_value = "false")]
+    #[clap(short, long, default_value = "false")]
     delete: bool,
+    #[clap(flatten)]
+    select: super::SelectArgs,
+}
+
+impl PriorityArgs {
+    /// Split the arguments into the selected ids and the priority.
+    /// note: a trailing uppercase letter is the priority, e.g. `3 5 B` or `B --project acme`,
+    /// like in todo.sh, while a lowercase one is a uid, e.g. `3 b`
+    fn ids_and_priority(&self) -> Result<(Vec<TaskSelector>, Option<TaskPriority>)> {
+        let mut args = self.args.as_slice();
+        let mut priority = None;
+        if let Some((last, rest)) = args.split_last() {
+            if let [c] = last.as_bytes() {
+                if c.is_ascii_uppercase() {
+                    priority = Some(TaskPriority::from(char::from(*c)));
+                    args = rest;
+                }
+            }
+        }
+        let ids = args
+            .iter()
+            .map(|s| s.parse::<TaskSelector>())
+            .collect::<Result<Vec<_>, _>>()?;
+        let priority = match (self.delete, priority) {
+            (true, Some(_)) => anyhow::bail!("A priority can not be combined with --delete"),
+            (true, None) => None,
+            (false, p) => Some(p.unwrap_or(TaskPriority::A)),
+        };
+        Ok((ids, priority))
+    }
 }
 
 #[tracing::instrument(parent = None, skip(manager))]
 pub(crate) async fn cmd_priority(manager: &TodoManager, options: PriorityArgs) -> Result<()> {
-    let priority = if options.delete {
-        None
-    } else {
-        Some(options.priority)
-    };
-    tracing::info!("Setting priority at: {} {:?}", options.id, priority);
-    match manager.set_priority_async(options.id, priority).await {
-        Ok((id, task)) => {
-            tracing::info!("Priority setted at: {} {:?}", id, task);
+    let (ids, priority) = options.ids_and_priority()?;
+    let formatted = super::format_ids(&ids);
+    tracing::info!("Setting priority at: {} {:?}", formatted, priority);
+    let selection = options.select.selection(ids)?;
+    match manager.set_priority_many_async(selection, priority).await {
+        Ok(updated) => {
+            tracing::info!("Priority setted at: {:?}", updated);
+            if updated.is_empty() {
+                eprintln!("No matching tasks");
+            }
             Ok(())
         }
         Err(e) => {
             tracing::error!("Error: {}", e);
             match &e {
-                TodoTxtRsError::InvalidIndex => {
-                    eprintln!("Invalid index: {}", options.id);
+                TodoTxtRsError::InvalidIndex | TodoTxtRsError::NotFound => {
+                    eprintln!("Invalid index: {}", formatted);
                 }
                 e => {
                     eprintln!("Error: {}", e);
@@ -41,3 +71,34 @@ pub(crate) async fn cmd_priority(manager: &TodoManager, options: PriorityArgs) -
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use clap::Parser;
+    use todo_txt_manager::TaskRef;
+
+    fn parse(args: &[&str]) -> (Vec<TaskSelector>, Option<TaskPriority>) {
+        PriorityArgs::parse_from(std::iter::once("pri").chain(args.iter().copied()))
+            .ids_and_priority()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_ids_and_priority() {
+        let uid = |uid: &str| TaskSelector::Ref(TaskRef::Uid(uid.to_string()));
+        assert_eq!(
+            parse(&["3", "5", "B"]),
+            (vec![3.into(), 5.into()], Some(TaskPriority::B))
+        );
+        assert_eq!(parse(&["a"]), (vec![uid("a")], Some(TaskPriority::A)));
+        assert_eq!(
+            parse(&["3", "b"]),
+            (vec![3.into(), uid("b")], Some(TaskPriority::A))
+        );
+        assert_eq!(parse(&["C"]), (vec![], Some(TaskPriority::C)));
+        assert!(PriorityArgs::parse_from(["pri", "3", "B", "--delete"])
+            .ids_and_priority()
+            .is_err());
+    }
+}
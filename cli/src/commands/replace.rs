@@ -1,10 +1,11 @@
 use anyhow::Result;
-use todo_txt_manager::TodoManager;
+use todo_txt_manager::{TaskRef, TodoManager};
 use todo_txt_model::prelude::*;
 
-#[derive(Debug, Default, clap::Parser)]
+#[derive(Debug, clap::Parser)]
 pub(crate) struct ReplaceArgs {
-    id: usize,
+    /// Task id or uid (the value of its `id:` tag) to replace.
+    id: TaskRef,
     /// Task string to add.
     destenations: Vec<String>,
     /// Task status. If task string contains status, it will be ignored.
@@ -31,8 +32,12 @@ pub(crate) async fn cmd_replace(manager: &TodoManager, options: ReplaceArgs) ->
         Ok(task) => task,
         Err(e) => {
             tracing::error!("Failed to parse task: {}", e);
-            if let TodoTxtRsError::Syntax = e {
-                eprintln!("Invalid task syntax: {}", destenation)
+            if let TodoTxtRsError::Syntax(syntax) = &e {
+                eprintln!(
+                    "Invalid task syntax: {}\n{}",
+                    syntax,
+                    super::render_syntax_error(&destenation, syntax)
+                )
             }
             return Err(e.into());
         }
@@ -53,7 +58,7 @@ pub(crate) async fn cmd_replace(manager: &TodoManager, options: ReplaceArgs) ->
         task.description.context.extend_from_slice(contexts);
     }
     tracing::info!("Replacing task at: {}, {:?}", options.id, task);
-    let (id, old, new) = manager.replace_async(options.id, task).await?;
+    let (id, old, new) = manager.replace_async(options.id.clone(), task).await?;
     tracing::info!("Replaced task: {}, old: {:?}, new: {:?}", id, old, new);
     Ok(())
 }
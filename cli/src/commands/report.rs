@@ -0,0 +1,60 @@
+use anyhow::Result;
+use todo_txt_manager::{ReportEntry, TodoManager};
+
+#[derive(Debug, Default, clap::Parser)]
+pub(crate) struct ReportArgs {
+    /// Print the report history without adding a new entry.
+    #[clap(long, default_value = "false")]
+    no_record: bool,
+    /// Show only the last entries.
+    #[clap(long)]
+    last: Option<usize>,
+}
+
+#[tracing::instrument(parent = None, skip(manager))]
+pub(crate) async fn cmd_report(manager: &TodoManager, options: ReportArgs) -> Result<()> {
+    if !options.no_record {
+        let entry = manager.report_async().await?;
+        tracing::info!("Reported: {}", entry);
+    }
+    let entries = manager.read_report_async().await?;
+    if entries.is_empty() {
+        eprintln!("No reports in {}", manager.get_report_path().display());
+        return Ok(());
+    }
+    let skip = options
+        .last
+        .map_or(0, |last| entries.len().saturating_sub(last));
+    println!("{}", trend_table(&entries, skip).join("\n"));
+    Ok(())
+}
+
+/// Rows of the trend table, with the changes since the previous entry.
+/// note: the first `skip` entries are only used for the changes of the next one
+fn trend_table(entries: &[ReportEntry], skip: usize) -> Vec<String> {
+    let mut rows = vec![format!(
+        "{:<19}  {:>6}  {:>6}  {:>7}  {:>7}",
+        "date", "open", "done", "Δopen", "Δdone"
+    )];
+    let previous = std::iter::once(None).chain(entries.iter().map(Some));
+    for (entry, previous) in entries.iter().zip(previous).skip(skip) {
+        let change = |now: usize, before: Option<usize>| {
+            before.map_or_else(String::new, |before| {
+                format!("{:+}", now as i64 - before as i64)
+            })
+        };
+        rows.push(
+            format!(
+                "{:<19}  {:>6}  {:>6}  {:>7}  {:>7}",
+                entry.time.format("%Y-%m-%d %H:%M:%S").to_string(),
+                entry.open,
+                entry.done,
+                change(entry.open, previous.map(|p| p.open)),
+                change(entry.done, previous.map(|p| p.done)),
+            )
+            .trim_end()
+            .to_string(),
+        );
+    }
+    rows
+}
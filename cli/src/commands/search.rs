@@ -0,0 +1,81 @@
+use anyhow::Result;
+use std::io::IsTerminal;
+use todo_txt_manager::*;
+
+const HIGHLIGHT_START: &str = "\x1b[1;31m";
+const HIGHLIGHT_END: &str = "\x1b[0m";
+
+#[derive(Debug, clap::Parser)]
+pub(crate) struct SearchArgs {
+    /// Text to search for.
+    #[clap(required = true)]
+    patterns: Vec<String>,
+    /// Match whole words only.
+    #[clap(short, long, conflicts_with = "regex", default_value = "false")]
+    word: bool,
+    /// Treat the pattern as a regular expression.
+    #[clap(short = 'e', long, default_value = "false")]
+    regex: bool,
+    /// Match case.
+    #[clap(short, long, default_value = "false")]
+    case_sensitive: bool,
+    /// Also search projects, contexts and tags.
+    #[clap(short, long, default_value = "false")]
+    all_fields: bool,
+}
+
+#[tracing::instrument(parent = None, skip(manager))]
+pub(crate) async fn cmd_search(manager: &TodoManager, options: SearchArgs) -> Result<()> {
+    let pattern = options.patterns.join(" ");
+    let mode = if options.regex {
+        SearchMode::Regex
+    } else if options.word {
+        SearchMode::Word
+    } else {
+        SearchMode::Substring
+    };
+    let search = TextSearch::new(
+        &pattern,
+        SearchOptions {
+            mode,
+            case_sensitive: options.case_sensitive,
+            all_fields: options.all_fields,
+        },
+    )
+    .inspect_err(|e| eprintln!("Invalid pattern: {}", e))?;
+    let filter = Filter {
+        text: Some(search.clone()),
+        ..Default::default()
+    };
+    tracing::info!("Searching tasks with filter: {:?}", filter);
+    let tasks = manager.list_async(filter, Order::default()).await?;
+    tracing::info!("Found {} tasks", tasks.len());
+    let highlight = std::io::stdout().is_terminal();
+    for (idx, task) in tasks {
+        let line = todo_txt_serializer::to_string(&task);
+        if highlight {
+            println!(
+                "{} {}",
+                idx,
+                highlight_ranges(&line, &search.find_task_ranges(&task))
+            );
+        } else {
+            println!("{} {}", idx, line);
+        }
+    }
+    Ok(())
+}
+
+fn highlight_ranges(line: &str, ranges: &[std::ops::Range<usize>]) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut last = 0;
+    for range in ranges {
+        out.push_str(&line[last..range.start]);
+        out.push_str(HIGHLIGHT_START);
+        out.push_str(&line[range.clone()]);
+        out.push_str(HIGHLIGHT_END);
+        last = range.end;
+    }
+    out.push_str(&line[last..]);
+    out
+}
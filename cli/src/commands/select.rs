@@ -0,0 +1,53 @@
+use anyhow::Result;
+use todo_txt_manager::*;
+
+/// Options selecting every task matching a filter, instead of giving ids.
+#[derive(Debug, Default, clap::Args)]
+pub(crate) struct SelectArgs {
+    /// Select tasks with this priority: `A`, a range `A..C`, `any` or `none`.
+    #[clap(long, value_delimiter(','))]
+    priority: Option<Vec<PriorityFilter>>,
+    /// Select tasks in all of these projects.
+    #[clap(long, value_delimiter(','))]
+    project: Option<Vec<String>>,
+    /// Select tasks in all of these contexts.
+    #[clap(long, value_delimiter(','))]
+    context: Option<Vec<String>>,
+    /// Select tasks matching a query, e.g. `+work and due<today`.
+    #[clap(long)]
+    query: Option<Query>,
+}
+
+impl SelectArgs {
+    fn is_empty(&self) -> bool {
+        self.priority.is_none()
+            && self.project.is_none()
+            && self.context.is_none()
+            && self.query.is_none()
+    }
+
+    /// Tasks selected by `ids`, or by the filter options when no id is given.
+    pub(crate) fn selection(self, ids: Vec<TaskSelector>) -> Result<Selection> {
+        match (ids.is_empty(), self.is_empty()) {
+            (true, true) => anyhow::bail!("No task selected: give task ids or filter options"),
+            (false, false) => anyhow::bail!("Task ids can not be combined with filter options"),
+            (false, true) => Ok(ids.into()),
+            (true, false) => Ok(Filter {
+                priority: self.priority,
+                project: self.project,
+                context: self.context,
+                query: self.query,
+                ..Default::default()
+            }
+            .into()),
+        }
+    }
+}
+
+/// Ids as given on the command line, for messages.
+pub(crate) fn format_ids(ids: &[TaskSelector]) -> String {
+    ids.iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,94 @@
+use anyhow::Result;
+use todo_txt_manager::{GroupStats, Stats, StatsOptions, TodoManager};
+
+#[derive(Debug, Default, clap::Parser)]
+pub(crate) struct StatsArgs {
+    /// Print the statistics as JSON
+    #[clap(long, default_value = "false")]
+    json: bool,
+    /// Number of weeks of created and completed tasks to show, at most 1040
+    #[clap(
+        long,
+        default_value = "8",
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new()
+            .range(0..=todo_txt_manager::MAX_STATS_WEEKS as u64)
+    )]
+    weeks: usize,
+    /// Number of oldest open tasks to show
+    #[clap(long, default_value = "5")]
+    oldest: usize,
+}
+
+#[tracing::instrument(parent = None, skip(manager))]
+pub(crate) async fn cmd_stats(manager: &TodoManager, options: StatsArgs) -> Result<()> {
+    let stats = manager
+        .stats_async(StatsOptions {
+            weeks: options.weeks,
+            oldest: options.oldest,
+        })
+        .await?;
+    tracing::debug!("stats: {:?}", stats);
+    if options.json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+    } else {
+        print_tables(&stats);
+    }
+    Ok(())
+}
+
+fn print_tables(stats: &Stats) {
+    println!("open: {}  done: {}", stats.open, stats.done);
+    match stats.average_lead_time {
+        Some(days) => println!("average lead time: {:.1} days", days),
+        None => println!("average lead time: -"),
+    }
+    print_groups("project", &stats.projects);
+    print_groups("context", &stats.contexts);
+
+    println!(
+        "\n{:<10}  {:>7}  {:>9}  {:>4}",
+        "week", "created", "completed", "rate"
+    );
+    for week in &stats.weeks {
+        let rate = match week.completion_rate {
+            Some(rate) => format!("{:.0}%", rate * 100.0),
+            None => "-".to_string(),
+        };
+        println!(
+            "{:<10}  {:>7}  {:>9}  {:>4}",
+            week.start.to_string(),
+            week.created,
+            week.completed,
+            rate
+        );
+    }
+
+    if !stats.oldest_open.is_empty() {
+        println!("\n{:>4}  {:>5}  task", "id", "age");
+        for task in &stats.oldest_open {
+            println!("{:>4}  {:>4}d  {}", task.id, task.age, task.task);
+        }
+    }
+}
+
+fn print_groups(title: &str, groups: &[GroupStats]) {
+    if groups.is_empty() {
+        return;
+    }
+    let width = groups
+        .iter()
+        .map(|g| g.name.chars().count() + 1)
+        .max()
+        .unwrap_or(0)
+        .max(title.len());
+    println!("\n{:<width$}  {:>5}  {:>5}", title, "open", "done");
+    let sigil = if title == "project" { '+' } else { '@' };
+    for group in groups {
+        println!(
+            "{:<width$}  {:>5}  {:>5}",
+            format!("{}{}", sigil, group.name),
+            group.open,
+            group.done
+        );
+    }
+}
@@ -0,0 +1,10 @@
+use todo_txt_model::SyntaxError;
+
+/// Render `raw` with carets under the token the syntax error points at.
+pub(crate) fn render_syntax_error(raw: &str, error: &SyntaxError) -> String {
+    let start = error.span.start.min(raw.len());
+    let end = error.span.end.clamp(start, raw.len());
+    let column = raw.get(..start).map_or(start, |s| s.chars().count());
+    let width = raw.get(start..end).map_or(0, |s| s.chars().count()).max(1);
+    format!("{}\n{}{}", raw, " ".repeat(column), "^".repeat(width))
+}
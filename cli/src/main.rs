@@ -30,7 +30,11 @@ fn init_log(todo_dir: &std::path::Path) -> Result<(), todo_txt_model::prelude::T
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
-    let manager = todo_txt_manager::TodoManager::new()?;
+    let mut builder = todo_txt_manager::TodoManagerBuilder::new().with_config(args.config());
+    if let Some(config_file) = &args.config_file {
+        builder = builder.with_config_file(config_file);
+    }
+    let manager = builder.build()?.with_lenient(true);
     let data_path = manager.get_data_dir();
     init_log(data_path)?;
     tracing::info!("args: {:?}", args);
@@ -56,6 +60,25 @@ async fn main() -> Result<()> {
         Some(SubCommand::Priority(options)) => commands::cmd_priority(&manager, options).await?,
         Some(SubCommand::Append(options)) => commands::cmd_append(&manager, options).await?,
         Some(SubCommand::Replace(options)) => commands::cmd_replace(&manager, options).await?,
+        Some(SubCommand::Search(options)) => {
+            need_show_after = false;
+            commands::cmd_search(&manager, options).await?
+        }
+        Some(SubCommand::Archive(options)) => commands::cmd_archive(&manager, options).await?,
+        Some(SubCommand::Report(options)) => {
+            need_show_after = false;
+            commands::cmd_report(&manager, options).await?
+        }
+        Some(SubCommand::Stats(options)) => {
+            need_show_after = false;
+            commands::cmd_stats(&manager, options).await?
+        }
+        Some(SubCommand::Lists) => {
+            need_show_after = false;
+            commands::cmd_lists(&manager).await?
+        }
+        Some(SubCommand::Undo) => commands::cmd_undo(&manager).await?,
+        Some(SubCommand::Redo) => commands::cmd_redo(&manager).await?,
         _ => {
             need_show_after = false;
             commands::cmd_list(&manager, ListArgs::default()).await?
@@ -76,16 +99,16 @@ enum SubCommand {
     /// Add a new task
     #[clap(visible_alias = "a")]
     Add(AddArgs),
-    /// Done a task
+    /// Done tasks by ids, ranges or filter
     #[clap(visible_alias = "do")]
     Done(DoneArgs),
-    /// Undone a task
+    /// Undone tasks by ids, ranges or filter
     #[clap(visible_alias = "un")]
     Undone(DoneArgs),
-    /// Delete a task
+    /// Delete tasks by ids, ranges or filter
     #[clap(visible_alias = "de")]
     Delete(DeleteArgs),
-    /// Set the priority of a task
+    /// Set the priority of tasks by ids, ranges or filter
     #[clap(visible_alias = "pri")]
     Priority(PriorityArgs),
     /// Append text to a task
@@ -94,11 +117,65 @@ enum SubCommand {
     /// Replace a task
     #[clap(visible_alias = "rep")]
     Replace(ReplaceArgs),
+    /// Search tasks by text
+    #[clap(visible_alias = "s")]
+    Search(SearchArgs),
+    /// Move done tasks from todo.txt to done.txt
+    #[clap(visible_alias = "ar")]
+    Archive(ArchiveArgs),
+    /// Record the number of open and done tasks in report.txt and show the trend
+    Report(ReportArgs),
+    /// Show counts per project and context, lead time and weekly throughput
+    Stats(StatsArgs),
+    /// Show the lists of the data directory
+    Lists,
+    /// Undo the last change
+    Undo,
+    /// Redo the last undone change
+    Redo,
 }
 
 #[derive(Debug, clap::Parser)]
 #[clap(name = "todo", author, about, version)]
 struct Args {
+    /// Configuration file, todo.cfg or TOML, instead of the default ones
+    #[clap(short = 'd', long = "config")]
+    config_file: Option<std::path::PathBuf>,
+    /// Work on this list, e.g. `work` for work.txt and work.done.txt, instead of todo.txt
+    #[clap(short = 'l', long)]
+    list: Option<String>,
+    /// Set the creation date of added tasks
+    #[clap(short = 't', conflicts_with = "no_date_on_add")]
+    date_on_add: bool,
+    /// Do not set the creation date of added tasks
+    #[clap(short = 'T')]
+    no_date_on_add: bool,
+    /// Move done tasks to done.txt on every change
+    #[clap(short = 'A', conflicts_with = "no_auto_archive")]
+    auto_archive: bool,
+    /// Keep done tasks in todo.txt until `archive`
+    #[clap(short = 'a')]
+    no_auto_archive: bool,
+    /// Keep the ids of tasks when others are deleted or done, until `archive`
+    #[clap(short = 'N', conflicts_with = "no_preserve_line_numbers")]
+    preserve_line_numbers: bool,
+    /// Renumber tasks after every change
+    #[clap(short = 'n')]
+    no_preserve_line_numbers: bool,
     #[clap(subcommand)]
     subcmd: Option<SubCommand>,
 }
+
+impl Args {
+    /// Settings of the flags, overriding the configuration files and the environment.
+    fn config(&self) -> todo_txt_manager::Config {
+        let flag = |on: bool, off: bool| (on || off).then_some(on);
+        todo_txt_manager::Config {
+            date_on_add: flag(self.date_on_add, self.no_date_on_add),
+            auto_archive: flag(self.auto_archive, self.no_auto_archive),
+            preserve_line_numbers: flag(self.preserve_line_numbers, self.no_preserve_line_numbers),
+            list: self.list.clone(),
+            ..Default::default()
+        }
+    }
+}
@@ -26,7 +26,7 @@ pub(crate) struct App {
 
 impl App {
     pub(crate) fn new() -> Result<Self> {
-        let todo_manager = Box::pin(todo_txt_manager::TodoManager::new()?);
+        let todo_manager = Box::pin(todo_txt_manager::TodoManager::new()?.with_lenient(true));
         Ok(Self {
             current_window: CurrentWindow::Main,
             main_window_state: windows::main_window::MainWindowState::new(todo_manager.clone()),
@@ -0,0 +1,318 @@
+use crate::lists::ListFiles;
+use crate::{ArchivePolicy, Config, FileStorage, TodoManager, DEFAULT_LIST, DEFAULT_LOCK_TIMEOUT};
+use todo_txt_model::prelude::*;
+
+/// Configuration of a `TodoManager` with explicit paths.
+///
+/// Relative file paths are resolved against the data directory, absolute ones are used as
+/// they are. Without a `TODO_DIR` setting, the directory is `XDG_DATA_HOME/todo`, then
+/// `.todo` in the current directory.
+/// note: see `Config` for the order in which configuration files, the environment and the
+/// settings given here override each other
+#[derive(Debug, Clone)]
+pub struct TodoManagerBuilder {
+    config: Config,
+    config_file: Option<std::path::PathBuf>,
+    load_env: bool,
+}
+
+impl Default for TodoManagerBuilder {
+    fn default() -> Self {
+        Self {
+            config: Config::default(),
+            config_file: None,
+            load_env: true,
+        }
+    }
+}
+
+impl TodoManagerBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Directory of the files given as relative paths, the lock, the journal and the history.
+    pub fn with_data_dir(mut self, data_dir: impl Into<std::path::PathBuf>) -> Self {
+        self.config.todo_dir = Some(data_dir.into());
+        self
+    }
+
+    pub fn with_todo_file(mut self, todo_file: impl Into<std::path::PathBuf>) -> Self {
+        self.config.todo_file = Some(todo_file.into());
+        self
+    }
+
+    pub fn with_done_file(mut self, done_file: impl Into<std::path::PathBuf>) -> Self {
+        self.config.done_file = Some(done_file.into());
+        self
+    }
+
+    pub fn with_report_file(mut self, report_file: impl Into<std::path::PathBuf>) -> Self {
+        self.config.report_file = Some(report_file.into());
+        self
+    }
+
+    /// Work on the list `name`, e.g. `work` for work.txt, work.done.txt and work.report.txt.
+    /// note: a list other than `todo` takes the place of the todo, done and report files
+    pub fn with_list(mut self, name: impl Into<String>) -> Self {
+        self.config.list = Some(name.into());
+        self
+    }
+
+    /// Settings overriding the configuration files and the environment, e.g. CLI flags.
+    pub fn with_config(mut self, config: Config) -> Self {
+        self.config = self.config.merge(config);
+        self
+    }
+
+    /// Read this todo.cfg or TOML file instead of the default configuration files.
+    pub fn with_config_file(mut self, config_file: impl Into<std::path::PathBuf>) -> Self {
+        self.config_file = Some(config_file.into());
+        self
+    }
+
+    /// Whether to load `.env`, the environment variables and the default configuration files.
+    /// note: without the environment the data directory defaults to `.todo` in the current
+    /// directory, and only the file given by `with_config_file` is read
+    pub fn with_env(mut self, load_env: bool) -> Self {
+        self.load_env = load_env;
+        self
+    }
+
+    /// Merge the settings, check that the paths can be used and create the manager.
+    /// note: missing files and directories are fine, they are created by the first write
+    pub fn build(self) -> Result<TodoManager> {
+        if self.load_env {
+            let _ = dotenvy::dotenv();
+        }
+        let mut config = Config::default();
+        if let Some(file) = &self.config_file {
+            config = config.merge(Config::from_file(file)?);
+        } else if self.load_env {
+            config = config.merge(Config::from_default_files()?);
+        }
+        if self.load_env {
+            config = config.merge(Config::from_env()?);
+        }
+        let config = config.merge(self.config);
+        tracing::debug!("config: {:?}", config);
+
+        let todo_dir = match config.todo_dir {
+            Some(dir) => dir,
+            None => default_data_dir(self.load_env)?,
+        };
+        if todo_dir.exists() && !todo_dir.is_dir() {
+            return Err(invalid_path(&todo_dir, "not a directory"));
+        }
+
+        let list = config.list.unwrap_or_else(|| DEFAULT_LIST.to_string());
+        let defaults = ListFiles::new(DEFAULT_LIST)?;
+        let default_files = [
+            config.todo_file.unwrap_or_else(|| defaults.todo.into()),
+            config.done_file.unwrap_or_else(|| defaults.done.into()),
+            config.report_file.unwrap_or_else(|| defaults.report.into()),
+        ];
+        check_files(&todo_dir, &default_files)?;
+        let [todo_file, done_file, report_file] = default_files;
+        let default_list = ListFiles {
+            todo: path_name(&todo_file),
+            done: path_name(&done_file),
+            report: path_name(&report_file),
+            history: defaults.history,
+        };
+        let list_files = if list == DEFAULT_LIST {
+            default_list.clone()
+        } else {
+            let files = ListFiles::new(&list)?;
+            check_files(
+                &todo_dir,
+                &[&files.todo, &files.done, &files.report].map(std::path::PathBuf::from),
+            )?;
+            files
+        };
+
+        Ok(TodoManager {
+            storage: std::sync::Arc::new(FileStorage::new(&todo_dir)),
+            todo_dir,
+            list,
+            todo_file: list_files.todo,
+            done_file: list_files.done,
+            report_file: list_files.report,
+            history_file: list_files.history,
+            default_list,
+            history_depth: config.history_depth.unwrap_or(crate::DEFAULT_HISTORY_DEPTH),
+            lock_timeout: DEFAULT_LOCK_TIMEOUT,
+            auto_id: config.auto_id.unwrap_or(false),
+            date_on_add: config.date_on_add.unwrap_or(true),
+            preserve_line_numbers: config.preserve_line_numbers.unwrap_or(false),
+            lenient: false,
+            archive_policy: match config.auto_archive {
+                Some(false) => ArchivePolicy::Manual,
+                _ => ArchivePolicy::Auto,
+            },
+        })
+    }
+}
+
+/// Check that the todo, done and report `files` can be used and are distinct.
+fn check_files(todo_dir: &std::path::Path, files: &[std::path::PathBuf; 3]) -> Result<()> {
+    let mut resolved: Vec<(&str, std::path::PathBuf)> = Vec::with_capacity(files.len());
+    for (kind, file) in ["todo", "done", "report"].into_iter().zip(files) {
+        if file.as_os_str().is_empty() {
+            return Err(invalid_path(file, &format!("empty {} file", kind)));
+        }
+        let path = normalize(&todo_dir.join(file));
+        if path.is_dir() {
+            return Err(invalid_path(&path, "is a directory"));
+        }
+        if file.to_str().is_none() {
+            return Err(invalid_path(file, "not valid UTF-8"));
+        }
+        if let Some((other, _)) = resolved.iter().find(|(_, p)| *p == path) {
+            return Err(invalid_path(
+                &path,
+                &format!("used as both {} and {} file", other, kind),
+            ));
+        }
+        resolved.push((kind, path));
+    }
+    Ok(())
+}
+
+fn default_data_dir(load_env: bool) -> Result<std::path::PathBuf> {
+    if load_env {
+        if let Ok(home) = std::env::var("XDG_DATA_HOME") {
+            return Ok(std::path::PathBuf::from(home).join("todo"));
+        }
+    }
+    Ok(std::env::current_dir()?.join(".todo"))
+}
+
+/// Name of a file in the storage.
+/// note: checked to be valid UTF-8 by `build`
+fn path_name(file: &std::path::Path) -> String {
+    file.to_string_lossy().into_owned()
+}
+
+/// `path` without `.` components, to compare the files without touching the disk.
+fn normalize(path: &std::path::Path) -> std::path::PathBuf {
+    path.components()
+        .filter(|c| !matches!(c, std::path::Component::CurDir))
+        .collect()
+}
+
+fn invalid_path(path: &std::path::Path, reason: &str) -> TodoTxtRsError {
+    TodoTxtRsError::InvalidPath(path.to_path_buf(), reason.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("todo-txt-rs-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_build_with_explicit_paths() {
+        let dir = temp_dir("builder");
+        let archive = temp_dir("builder-archive").join("archive.txt");
+        let manager = TodoManagerBuilder::new()
+            .with_env(false)
+            .with_data_dir(&dir)
+            .with_todo_file("tasks.txt")
+            .with_done_file(&archive)
+            .build()
+            .unwrap();
+        assert_eq!(manager.get_data_dir(), dir);
+        assert_eq!(manager.get_todo_path(), dir.join("tasks.txt"));
+        assert_eq!(manager.get_done_path(), archive);
+        assert_eq!(manager.get_report_path(), dir.join("report.txt"));
+
+        for line in ["2021-01-01 a task", "x 2021-01-02 2021-01-01 a done task"] {
+            manager
+                .add(todo_txt_serializer::from_str(line).unwrap())
+                .unwrap();
+        }
+        assert_eq!(
+            std::fs::read_to_string(dir.join("tasks.txt")).unwrap(),
+            "2021-01-01 a task\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(&archive).unwrap(),
+            "x 2021-01-02 2021-01-01 a done task\n"
+        );
+    }
+
+    #[test]
+    fn test_build_rejects_unusable_paths() {
+        let dir = temp_dir("builder-invalid");
+        let builder = TodoManagerBuilder::new()
+            .with_env(false)
+            .with_data_dir(&dir);
+        let invalid = |builder: TodoManagerBuilder| {
+            matches!(builder.build(), Err(TodoTxtRsError::InvalidPath(_, _)))
+        };
+
+        assert!(invalid(builder.clone().with_done_file("./todo.txt")));
+        assert!(invalid(
+            builder.clone().with_report_file(dir.join("done.txt"))
+        ));
+        assert!(invalid(builder.clone().with_todo_file("")));
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        assert!(invalid(builder.clone().with_todo_file("sub")));
+        std::fs::write(dir.join("file"), "").unwrap();
+        assert!(invalid(builder.clone().with_data_dir(dir.join("file"))));
+        assert!(builder.build().is_ok());
+    }
+
+    #[test]
+    fn test_build_with_config_file() {
+        let dir = temp_dir("builder-config");
+        let config_file = dir.join("todo.cfg");
+        std::fs::write(
+            &config_file,
+            format!(
+                "export TODO_DIR={}\nexport TODO_FILE=tasks.txt\nexport TODOTXT_DATE_ON_ADD=0\nexport TODOTXT_AUTO_ARCHIVE=0\n",
+                dir.display()
+            ),
+        )
+        .unwrap();
+        let manager = TodoManagerBuilder::new()
+            .with_env(false)
+            .with_config_file(&config_file)
+            .with_config(Config {
+                auto_archive: Some(true),
+                ..Default::default()
+            })
+            .build()
+            .unwrap();
+        assert_eq!(manager.get_todo_path(), dir.join("tasks.txt"));
+        assert_eq!(manager.archive_policy, ArchivePolicy::Auto);
+
+        let task = manager
+            .add(todo_txt_serializer::from_str("a task").unwrap())
+            .unwrap();
+        assert_eq!(task.created_date, None);
+
+        let work = TodoManagerBuilder::new()
+            .with_env(false)
+            .with_config_file(&config_file)
+            .with_list("work")
+            .build()
+            .unwrap();
+        assert_eq!(work.get_todo_path(), dir.join("work.txt"));
+        assert_eq!(work.get_done_path(), dir.join("work.done.txt"));
+        assert!(matches!(
+            TodoManagerBuilder::new()
+                .with_env(false)
+                .with_data_dir(&dir)
+                .with_list("a/b")
+                .build(),
+            Err(TodoTxtRsError::InvalidList(_))
+        ));
+    }
+}
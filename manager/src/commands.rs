@@ -1,179 +1,232 @@
-mod add;
-mod append;
-mod delete;
-mod done;
+pub(crate) mod add;
+pub(crate) mod append;
+pub(crate) mod archive;
+pub(crate) mod delete;
+pub(crate) mod done;
 pub(crate) mod list;
-mod priority;
+pub(crate) mod priority;
 mod replace;
+pub(crate) mod report;
+pub(crate) mod stats;
 
 use todo_txt_model::prelude::*;
 use todo_txt_serializer::prelude::*;
 
-#[cfg(feature = "rt_async_std")]
-use async_std::io::BufReadExt as AsyncRTBufReadExt;
-#[cfg(feature = "rt_smol")]
-use smol::io::{AsyncBufReadExt as AsyncRTBufReadExt, AsyncWriteExt, BufWriter};
-#[cfg(feature = "rt_tokio")]
-use tokio::io::{AsyncBufReadExt as AsyncRTBufReadExt, AsyncWriteExt, BufWriter};
-
-#[tracing::instrument(parent = None, skip(reader))]
-pub fn read_tasks<R>(reader: &mut R) -> Result<Vec<Task>>
-where
-    R: std::io::BufRead,
-{
-    let mut out = Vec::new();
-    let mut line = String::new();
-    while reader.read_line(&mut line).is_ok() {
-        if line.is_empty() {
-            tracing::debug!("empty line, breaking");
-            break;
-        }
-        tracing::debug!("line: {:?}", line);
-        let task = from_str(&line)?;
-        tracing::debug!("task: {:?}", task);
-        line.clear();
-        out.push(task);
-    }
-    Ok(out)
+/// Parse the lines of a file, failing on the first line that is not a task.
+/// note: with `blank`, blank lines are read as placeholders instead of failing
+#[tracing::instrument(parent = None, skip(lines))]
+pub(crate) fn parse_tasks(lines: &[String], blank: bool) -> Result<Vec<Task>> {
+    lines
+        .iter()
+        .enumerate()
+        .map(|(idx, line)| {
+            tracing::debug!("line: {:?}", line);
+            if blank && line.trim().is_empty() {
+                return Ok(placeholder());
+            }
+            from_str(line).map_err(|e| e.with_line(idx + 1))
+        })
+        .collect()
 }
 
-#[cfg(any(feature = "rt_async_std", feature = "rt_tokio", feature = "rt_smol"))]
-#[tracing::instrument(parent = None, skip(reader))]
-pub async fn read_tasks_async<R>(reader: &mut R) -> Result<Vec<Task>>
-where
-    R: AsyncRTBufReadExt + Unpin,
-{
-    let mut out = Vec::new();
-    let mut line = String::new();
-    while reader.read_line(&mut line).await.is_ok() {
-        if line.is_empty() {
-            tracing::debug!("empty line, breaking");
-            break;
-        }
-        tracing::debug!("line: {:?}", line);
-        let task = from_str(&line)?;
-        tracing::debug!("task: {:?}", task);
-        line.clear();
-        out.push(task);
-    }
-    Ok(out)
+/// Task standing for a blank line, which keeps the index of the tasks after it.
+/// note: it is written back as an empty line
+pub(crate) fn placeholder() -> Task {
+    Task::default()
 }
 
-#[tracing::instrument(parent = None, skip(writer))]
-pub(crate) fn write_task<W: std::io::Write>(
-    writer: &mut std::io::BufWriter<W>,
-    task: &Task,
-) -> Result<()> {
-    use std::io::Write;
-    writeln!(writer, "{}", todo_txt_serializer::to_string(task))?;
-    Ok(())
+pub(crate) fn is_placeholder(task: &Task) -> bool {
+    *task == Task::default()
 }
 
-#[cfg(any(feature = "rt_tokio", feature = "rt_smol"))]
-#[tracing::instrument(parent = None, skip(writer))]
-pub(crate) async fn write_task_async<W>(writer: &mut BufWriter<W>, task: &Task) -> Result<()>
-where
-    W: AsyncWriteExt + Unpin,
-{
-    use std::io::Write;
-
-    let mut buf = Vec::new();
-    writeln!(buf, "{}", todo_txt_serializer::to_string(task))?;
-    writer.write_all(&buf).await?;
-    Ok(())
+/// A line that could not be parsed as a task.
+#[derive(Debug)]
+pub struct Diagnostic {
+    pub file: std::path::PathBuf,
+    /// 1-based line number
+    pub line: usize,
+    pub raw: String,
+    pub error: TodoTxtRsError,
 }
 
-#[cfg(feature = "rt_async_std")]
-#[tracing::instrument(parent = None, skip(writer))]
-pub(crate) async fn write_task_async<W>(
-    writer: &mut async_std::io::BufWriter<W>,
-    task: &Task,
-) -> Result<()>
-where
-    W: async_std::io::Write + Unpin,
-{
-    use async_std::io::WriteExt;
-    writeln!(writer, "{}", todo_txt_serializer::to_string(task)).await?;
-    Ok(())
+/// A line written back as it is when its file is rewritten in lenient mode: a line that can
+/// not be parsed, or a blank line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct KeptLine {
+    /// Number of tasks before it in its file, it is written before the task that followed it
+    pub(crate) before: usize,
+    pub(crate) raw: String,
 }
 
-#[tracing::instrument(parent = None)]
-pub fn read_tasks_from_file(file: &std::path::Path) -> Result<Vec<Task>> {
-    let mut reader = std::io::BufReader::new(
-        std::fs::OpenOptions::new()
-            .read(true)
-            .write(true)
-            .create(true)
-            .truncate(false)
-            .open(file)?,
-    );
-    crate::commands::read_tasks(&mut reader)
+/// Where the tasks numbered by `read_all_tasks` come from, and the lines kept around them.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct FileLayout {
+    pub(crate) todo_len: usize,
+    pub(crate) done_len: usize,
+    pub(crate) todo_kept: Vec<KeptLine>,
+    pub(crate) done_kept: Vec<KeptLine>,
 }
 
-#[cfg(any(feature = "rt_async_std", feature = "rt_tokio", feature = "rt_smol"))]
-#[tracing::instrument(parent = None)]
-pub async fn read_tasks_from_file_async(file: &std::path::Path) -> Result<Vec<Task>> {
-    #[cfg(feature = "rt_async_std")]
-    use async_std::{fs::OpenOptions, io::BufReader};
-    #[cfg(feature = "rt_smol")]
-    use smol::{fs::OpenOptions, io::BufReader};
-    #[cfg(feature = "rt_tokio")]
-    use tokio::{fs::OpenOptions, io::BufReader};
-    let mut reader = BufReader::new(
-        OpenOptions::new()
-            .read(true)
-            .write(true)
-            .create(true)
-            .truncate(false)
-            .open(file)
-            .await?,
-    );
-    crate::commands::read_tasks_async(&mut reader).await
+impl FileLayout {
+    /// Indexes of the tasks read from todo.txt
+    pub(crate) fn todo_ids(&self) -> std::ops::Range<usize> {
+        1..self.todo_len + 1
+    }
+
+    /// Indexes of the tasks read from done.txt
+    pub(crate) fn done_ids(&self) -> std::ops::Range<usize> {
+        self.todo_len + 1..self.todo_len + self.done_len + 1
+    }
 }
 
-#[tracing::instrument(parent = None, skip(tasks))]
-pub fn write_tasks<'a>(file: &std::path::Path, tasks: &[Task]) -> Result<()> {
-    use std::io::Write;
-    let mut writer = std::io::BufWriter::new(
-        std::fs::OpenOptions::new()
-            .write(true)
-            .truncate(true)
-            .open(file)?,
-    );
-
-    for task in tasks {
-        write_task(&mut writer, task)?;
+/// Parse the lines of `file`, collecting the lines that can not be parsed instead of failing.
+/// note: blank lines are kept, or read as placeholders with `blank`
+#[tracing::instrument(parent = None, skip(lines))]
+pub(crate) fn parse_tasks_lenient(
+    file: &std::path::Path,
+    lines: &[String],
+    blank: bool,
+) -> crate::ReadTasksResult {
+    let mut out = Vec::new();
+    let mut diagnostics = Vec::new();
+    let mut kept = Vec::new();
+    for (idx, raw) in lines.iter().enumerate() {
+        let line_no = idx + 1;
+        if raw.trim().is_empty() {
+            if blank {
+                out.push(placeholder());
+            } else {
+                tracing::debug!("blank line {}, keeping", line_no);
+                kept.push(KeptLine {
+                    before: out.len(),
+                    raw: raw.clone(),
+                });
+            }
+            continue;
+        }
+        match from_str(raw) {
+            Ok(task) => out.push(task),
+            Err(error) => {
+                let error = error.with_line(line_no);
+                tracing::warn!("line {}: {:?}: {}", line_no, raw, error);
+                kept.push(KeptLine {
+                    before: out.len(),
+                    raw: raw.clone(),
+                });
+                diagnostics.push(Diagnostic {
+                    file: file.to_path_buf(),
+                    line: line_no,
+                    raw: raw.clone(),
+                    error,
+                });
+            }
+        }
     }
-    writer.flush()?;
-    Ok(())
+    (out, diagnostics, kept)
 }
 
-#[cfg(any(feature = "rt_async_std", feature = "rt_tokio", feature = "rt_smol"))]
-#[tracing::instrument(parent = None, skip(tasks))]
-pub async fn write_tasks_async(file: &std::path::Path, tasks: &[Task]) -> Result<()> {
-    #[cfg(feature = "rt_async_std")]
-    use async_std::{fs::OpenOptions, io::BufWriter};
-    #[cfg(feature = "rt_smol")]
-    use smol::{
-        fs::OpenOptions,
-        io::{AsyncWriteExt, BufWriter},
-    };
-    #[cfg(feature = "rt_tokio")]
-    use tokio::{
-        fs::OpenOptions,
-        io::{AsyncWriteExt, BufWriter},
-    };
-    let mut writer = BufWriter::new(
-        OpenOptions::new()
-            .write(true)
-            .truncate(true)
-            .open(file)
-            .await?,
-    );
-
-    for task in tasks {
-        write_task_async(&mut writer, task).await?;
+/// Lines of a file: the tasks, with each kept line put back before the task that followed it
+/// when the file was read, or at the end when that task is gone.
+/// note: `ids` are the indexes of the tasks read from the file, tasks from elsewhere or new
+/// ones do not move the kept lines
+pub(crate) fn file_lines(
+    tasks: &[(usize, Task)],
+    kept: &[KeptLine],
+    ids: std::ops::Range<usize>,
+) -> Vec<String> {
+    let mut kept = kept.iter().peekable();
+    let mut lines = Vec::with_capacity(tasks.len() + kept.len());
+    for (id, task) in tasks {
+        if ids.contains(id) {
+            while let Some(k) = kept.next_if(|k| ids.start + k.before <= *id) {
+                lines.push(k.raw.clone());
+            }
+        }
+        lines.push(todo_txt_serializer::to_string(task));
+    }
+    lines.extend(kept.map(|k| k.raw.clone()));
+    lines
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn numbered(tasks: &[Task]) -> Vec<(usize, Task)> {
+        tasks
+            .iter()
+            .cloned()
+            .enumerate()
+            .map(|(i, t)| (i + 1, t))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_lenient_and_keep_lines() {
+        let file = std::path::Path::new("todo.txt");
+        let lines = ["a task", "", "+home @phone", "x b task"].map(String::from);
+        assert!(matches!(
+            parse_tasks(&lines, false),
+            Err(TodoTxtRsError::Syntax(ref e)) if e.line == Some(2)
+        ));
+
+        let (tasks, diagnostics, kept) = parse_tasks_lenient(file, &lines, false);
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].file, file);
+        assert_eq!(diagnostics[0].line, 3);
+        assert_eq!(diagnostics[0].raw, "+home @phone");
+        assert_eq!(kept.len(), 2);
+
+        let tasks = numbered(&tasks);
+        assert_eq!(file_lines(&tasks, &kept, 1..3), lines);
+        assert_eq!(
+            file_lines(&tasks[..1], &kept, 1..3),
+            vec!["a task", "", "+home @phone"]
+        );
+    }
+
+    #[test]
+    fn test_kept_lines_follow_their_task() {
+        let file = std::path::Path::new("todo.txt");
+        let lines = ["a", "+bad", "b", "", "c"].map(String::from);
+        let (tasks, _, kept) = parse_tasks_lenient(file, &lines, false);
+        let tasks = numbered(&tasks);
+
+        // without the task above, the kept line stays above the task below it
+        assert_eq!(
+            file_lines(&tasks[1..], &kept, 1..4),
+            vec!["+bad", "b", "", "c"]
+        );
+        let without_b = [tasks[0].clone(), tasks[2].clone()];
+        assert_eq!(
+            file_lines(&without_b, &kept, 1..4),
+            vec!["a", "+bad", "", "c"]
+        );
+        // a task from the other file or a new one does not move them
+        let mut with_new = tasks.clone();
+        with_new.insert(0, (9, todo_txt_serializer::from_str("new").unwrap()));
+        assert_eq!(
+            file_lines(&with_new, &kept, 1..4),
+            vec!["new", "a", "+bad", "b", "", "c"]
+        );
+    }
+
+    #[test]
+    fn test_parse_blank_lines_as_placeholders() {
+        let file = std::path::Path::new("todo.txt");
+        let lines = ["a task", "", "+home @phone", "x b task"].map(String::from);
+        let (tasks, _, kept) = parse_tasks_lenient(file, &lines, true);
+        assert_eq!(tasks.len(), 3);
+        assert!(is_placeholder(&tasks[1]));
+        assert_eq!(file_lines(&numbered(&tasks), &kept, 1..4), lines);
+
+        let lines = ["a task", " ", "x b task"].map(String::from);
+        let tasks = parse_tasks(&lines, true).unwrap();
+        assert!(is_placeholder(&tasks[1]));
+        assert_eq!(
+            file_lines(&numbered(&tasks), &[], 1..4),
+            vec!["a task", "", "x b task"]
+        );
     }
-    writer.flush().await?;
-    Ok(())
 }
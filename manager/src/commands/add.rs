@@ -1,53 +1,65 @@
-use crate::TodoManager;
+use crate::{ArchivePolicy, TodoManager};
 use todo_txt_model::prelude::*;
+use todo_txt_serializer::prelude::*;
 
 impl TodoManager {
     #[tracing::instrument(parent = None, skip(self))]
     pub fn add(&self, mut new_task: Task) -> Result<Task> {
-        let file = match new_task.state {
-            TaskState::Todo => &self.todo_file,
-            TaskState::Done => &self.done_file,
+        let file = match (new_task.state, self.effective_archive_policy()) {
+            (TaskState::Done, ArchivePolicy::Auto) => &self.done_file,
+            _ => &self.todo_file,
         };
 
-        if new_task.created_date.is_none() {
-            new_task.created_date = Some(chrono::Utc::now().date_naive());
-        }
-        if new_task.state == TaskState::Done && new_task.completed_date.is_none() {
-            new_task.completed_date = Some(chrono::Utc::now().date_naive());
+        crate::commands::add::fill_dates(&mut new_task, self.date_on_add);
+
+        let _lock = self.lock()?;
+        if self.auto_id && new_task.uid().is_none() {
+            let tasks = self.read_all_tasks()?;
+            let uid = crate::task_ref::generate_uid(tasks.iter().map(|(_, t)| t));
+            new_task.set_uid(Some(uid));
         }
 
-        let tasks = {
-            let mut tasks = crate::commands::read_tasks_from_file(file)?;
-            tasks.push(new_task.clone());
-            tasks
+        let (tasks, kept) = {
+            let (tasks, _, kept) = self.read_tasks(file, self.lenient)?;
+            let mut tasks = tasks
+                .into_iter()
+                .enumerate()
+                .map(|(idx, t)| (idx + 1, t))
+                .collect::<Vec<_>>();
+            tasks.push((tasks.len() + 1, new_task.clone()));
+            (tasks, kept)
         };
         tracing::debug!("file:{:?}, all tasks: {:?}", file, tasks);
-        crate::commands::write_tasks(file, &tasks)?;
+        let before = self.snapshot()?;
+        let lines = crate::commands::file_lines(&tasks, &kept, 1..tasks.len());
+        let mut files = vec![(file.clone(), lines)];
+        // a new list shows up in `lists` once it has both its todo and done files
+        let other = if *file == self.todo_file {
+            &self.done_file
+        } else {
+            &self.todo_file
+        };
+        if self.list != crate::DEFAULT_LIST && !self.storage.names()?.contains(other) {
+            files.push((other.clone(), Vec::new()));
+        }
+        self.storage.save(&files)?;
+        self.record(&format!("add {}", to_string(&new_task)), before)?;
         Ok(new_task)
     }
 
-    #[cfg(any(feature = "rt_tokio", feature = "rt_async_std", feature = "rt_smol"))]
-    #[tracing::instrument(parent = None, skip(self))]
-    pub async fn add_async(&self, mut new_task: Task) -> Result<Task> {
-        let file = match new_task.state {
-            TaskState::Todo => &self.todo_file,
-            TaskState::Done => &self.done_file,
-        };
-
-        if new_task.created_date.is_none() {
-            new_task.created_date = Some(chrono::Utc::now().date_naive());
-        }
-        if new_task.state == TaskState::Done && new_task.completed_date.is_none() {
-            new_task.completed_date = Some(chrono::Utc::now().date_naive());
-        }
+    #[cfg(feature = "async")]
+    pub async fn add_async(&self, new_task: Task) -> Result<Task> {
+        self.unblock(move |manager| manager.add(new_task)).await
+    }
+}
 
-        let tasks = {
-            let mut tasks = crate::commands::read_tasks_from_file_async(file).await?;
-            tasks.push(new_task.clone());
-            tasks
-        };
-        tracing::debug!("file:{:?}, all tasks: {:?}", file, tasks);
-        crate::commands::write_tasks_async(file, &tasks).await?;
-        Ok(new_task)
+/// Set the creation date of a new task when `date_on_add`, and the completion date when it is
+/// done, to today unless they are given.
+pub(crate) fn fill_dates(task: &mut Task, date_on_add: bool) {
+    if date_on_add && task.created_date.is_none() {
+        task.created_date = Some(chrono::Utc::now().date_naive());
+    }
+    if task.state == TaskState::Done && task.completed_date.is_none() {
+        task.completed_date = Some(chrono::Utc::now().date_naive());
     }
 }
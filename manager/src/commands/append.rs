@@ -1,61 +1,132 @@
-use crate::TodoManager;
+use crate::{Selection, TaskRef, TodoManager};
 use todo_txt_model::prelude::*;
 
 impl TodoManager {
-    #[tracing::instrument(parent = None, skip(self))]
-    pub fn append(&self, task_id: usize, description: TaskDescription) -> Result<(usize, Task)> {
-        let tasks = self.list(
-            super::list::Filter::default(),
-            super::list::Order::default(),
-        )?;
-
-        let ((task_id, mut task), tasks) = self.take_task_by_id(task_id, tasks)?;
-
-        if !description.value.is_empty() {
-            task.description
-                .value
-                .push(todo_txt_serializer::TOKEN_SEPARATOR);
-            task.description.value.push_str(&description.value);
-        }
-        task.description.project.extend(description.project);
-        task.description.context.extend(description.context);
+    #[tracing::instrument(parent = None, skip(self, task_ref))]
+    pub fn append(
+        &self,
+        task_ref: impl Into<TaskRef>,
+        description: TaskDescription,
+    ) -> Result<(usize, Task)> {
+        let task_ref = task_ref.into();
+        tracing::debug!("task: {}", task_ref);
+        let _lock = self.lock()?;
+        let (tasks, layout) = self.read_for_write()?;
+
+        let ((task_id, mut task), tasks) = self.take_task(&task_ref, tasks)?;
+
+        append_description(&mut task, description);
 
         let tasks = self.set_task_at(task_id, task.clone(), tasks)?;
-        let (todos, dones) = self.split_tasks_todo_and_done(tasks)?;
-        crate::commands::write_tasks(&self.todo_file, &todos)?;
-        crate::commands::write_tasks(&self.done_file, &dones)?;
+        self.save_tasks(&format!("append {}", task_ref), tasks, &layout)?;
         Ok((task_id, task))
     }
 
-    #[cfg(any(feature = "rt_tokio", feature = "rt_async_std", feature = "rt_smol"))]
-    #[tracing::instrument(parent = None, skip(self))]
+    #[cfg(feature = "async")]
     pub async fn append_async(
         &self,
-        task_id: usize,
+        task_ref: impl Into<TaskRef>,
         description: TaskDescription,
     ) -> Result<(usize, Task)> {
-        let tasks = self
-            .list_async(
-                super::list::Filter::default(),
-                super::list::Order::default(),
-            )
-            .await?;
-
-        let ((task_id, mut task), tasks) = self.take_task_by_id(task_id, tasks)?;
-
-        if !description.value.is_empty() {
-            task.description
-                .value
-                .push(todo_txt_serializer::TOKEN_SEPARATOR);
-            task.description.value.push_str(&description.value);
-        }
-        task.description.project.extend(description.project);
-        task.description.context.extend(description.context);
+        let task_ref = task_ref.into();
+        self.unblock(move |manager| manager.append(task_ref, description))
+            .await
+    }
 
-        let tasks = self.set_task_at(task_id, task.clone(), tasks)?;
-        let (todos, dones) = self.split_tasks_todo_and_done(tasks)?;
-        crate::commands::write_tasks_async(&self.todo_file, &todos).await?;
-        crate::commands::write_tasks_async(&self.done_file, &dones).await?;
-        Ok((task_id, task))
+    /// Like `append` for every selected task, in a single read-modify-write cycle.
+    /// note: nothing is written when the selection is empty
+    #[tracing::instrument(parent = None, skip(self, selection))]
+    pub fn append_many(
+        &self,
+        selection: impl Into<Selection>,
+        description: TaskDescription,
+    ) -> Result<Vec<(usize, Task)>> {
+        let selection = selection.into();
+        tracing::debug!("selection: {:?}", selection);
+        self.edit_many("append", |tasks| {
+            append_selected(&selection, &description, tasks)
+        })
+    }
+
+    #[cfg(feature = "async")]
+    pub async fn append_many_async(
+        &self,
+        selection: impl Into<Selection>,
+        description: TaskDescription,
+    ) -> Result<Vec<(usize, Task)>> {
+        let selection = selection.into();
+        self.unblock(move |manager| manager.append_many(selection, description))
+            .await
+    }
+}
+
+pub(crate) fn append_selected(
+    selection: &Selection,
+    description: &TaskDescription,
+    tasks: &mut [(usize, Task)],
+) -> Result<Vec<(usize, Task)>> {
+    let positions = selection.positions(tasks, chrono::Utc::now().date_naive())?;
+    Ok(positions
+        .into_iter()
+        .map(|pos| {
+            append_description(&mut tasks[pos].1, description.clone());
+            (pos + 1, tasks[pos].1.clone())
+        })
+        .collect())
+}
+
+fn append_description(task: &mut Task, description: TaskDescription) {
+    if !description.value.is_empty() {
+        task.description
+            .value
+            .push(todo_txt_serializer::TOKEN_SEPARATOR);
+        task.description.value.push_str(&description.value);
+    }
+    task.description.project.extend(description.project);
+    task.description.context.extend(description.context);
+    task.description.tags.extend(description.tags);
+    if !task.description.tokens.is_empty() {
+        // a tag the task already has keeps its position with the new value
+        let layout = &task.description.tokens;
+        let tokens = description
+            .tokens
+            .into_iter()
+            .filter(|token| match token {
+                DescriptionToken::Tag(key, _) => !layout
+                    .iter()
+                    .any(|t| matches!(t, DescriptionToken::Tag(k, _) if k == key)),
+                _ => true,
+            })
+            .collect::<Vec<_>>();
+        task.description.tokens.extend(tokens);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{MemoryStorage, Storage, TodoManagerBuilder};
+
+    #[test]
+    fn test_append_over_existing_tag() {
+        let storage = MemoryStorage::new();
+        let manager = TodoManagerBuilder::new()
+            .with_env(false)
+            .build()
+            .unwrap()
+            .with_storage(storage.clone());
+        storage
+            .save(&[(
+                "todo.txt".to_string(),
+                vec!["pay rent due:2021-01-01".to_string()],
+            )])
+            .unwrap();
+        let appended = todo_txt_serializer::from_str("later due:2021-02-01").unwrap();
+        manager.append(1, appended.description).unwrap();
+
+        let line = storage.load("todo.txt").unwrap().remove(0);
+        assert_eq!(line, "pay rent due:2021-02-01 later");
+        let (_, task) = manager.get(1).unwrap();
+        assert_eq!(task.description.tags.get("due"), Some("2021-02-01"));
+        assert_eq!(todo_txt_serializer::to_string(&task), line);
     }
 }
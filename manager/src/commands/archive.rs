@@ -0,0 +1,139 @@
+use crate::TodoManager;
+use todo_txt_model::prelude::*;
+
+/// Where done tasks are kept after a mutation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+#[clap(rename_all = "snake_case")]
+pub enum ArchivePolicy {
+    /// Move done tasks to done.txt on every change.
+    #[default]
+    Auto,
+    /// Keep done tasks in todo.txt until `archive` is called, like todo.sh.
+    Manual,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ArchiveOptions {
+    /// Archive only tasks completed at least this many days ago.
+    /// note: done tasks without a completion date are always archived
+    pub older_than: Option<u32>,
+}
+
+impl ArchiveOptions {
+    fn matches(&self, task: &Task, today: chrono::NaiveDate) -> bool {
+        task.is_done()
+            && self.older_than.is_none_or(|days| {
+                task.completed_date
+                    .is_none_or(|d| (today - d).num_days() >= i64::from(days))
+            })
+    }
+}
+
+impl TodoManager {
+    /// Move done tasks from todo.txt to done.txt and return them.
+    /// note: the blank lines left by `with_preserve_line_numbers` are removed, like todo.sh does
+    #[tracing::instrument(parent = None, skip(self))]
+    pub fn archive(&self, options: ArchiveOptions) -> Result<Vec<Task>> {
+        let _lock = self.lock()?;
+        let (tasks, layout) = self.read_for_write()?;
+        let placeholders = tasks
+            .iter()
+            .any(|(_, t)| crate::commands::is_placeholder(t));
+        let ((todos, dones), archived) = split_archived(tasks, layout.done_ids(), &options);
+        if !archived.is_empty() || placeholders {
+            self.write_todo_and_done("archive", todos, dones, &layout)?;
+        }
+        Ok(archived)
+    }
+
+    #[cfg(feature = "async")]
+    pub async fn archive_async(&self, options: ArchiveOptions) -> Result<Vec<Task>> {
+        self.unblock(move |manager| manager.archive(options)).await
+    }
+}
+
+/// Split tasks read by `read_all_tasks` back into the files they came from.
+/// note: tasks are numbered in file order, `done_ids` are the indexes of those from done.txt
+/// note: tasks added since have an index above every read one and are written to todo.txt,
+/// like `add` does with a manual archive, and so are the tasks of done.txt marked undone
+pub(crate) fn split_tasks_by_file(
+    tasks: Vec<(usize, Task)>,
+    done_ids: std::ops::Range<usize>,
+) -> crate::SplitTasksResult {
+    tasks
+        .into_iter()
+        .partition(|(idx, t)| !done_ids.contains(idx) || !t.is_done())
+}
+
+/// Like `split_tasks_by_file`, also moving the tasks matching `options` from todo.txt to done.txt.
+/// note: placeholders are dropped
+fn split_archived(
+    tasks: Vec<(usize, Task)>,
+    done_ids: std::ops::Range<usize>,
+    options: &ArchiveOptions,
+) -> (crate::SplitTasksResult, Vec<Task>) {
+    let today = chrono::Utc::now().date_naive();
+    let tasks = tasks
+        .into_iter()
+        .filter(|(_, t)| !crate::commands::is_placeholder(t))
+        .collect();
+    let (todos, mut dones) = split_tasks_by_file(tasks, done_ids);
+    let (archived, todos): (Vec<_>, Vec<_>) = todos
+        .into_iter()
+        .partition(|(_, t)| options.matches(t, today));
+    dones.extend(archived.iter().cloned());
+    (
+        (todos, dones),
+        archived.into_iter().map(|(_, t)| t).collect(),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn task(description: &str, done: bool) -> Task {
+        let mut task = Task::default();
+        task.description.value = description.to_string();
+        if done {
+            task.state = TaskState::Done;
+        }
+        task
+    }
+
+    #[test]
+    fn test_split_tasks_by_file() {
+        let tasks = vec![
+            (1, task("a", true)),
+            (2, task("b", false)),
+            (3, task("c", true)),
+            (4, task("d", false)),
+            (5, task("e", true)),
+        ];
+        let (todos, dones) = split_tasks_by_file(tasks, 3..5);
+        let values = |tasks: &[(usize, Task)]| {
+            tasks
+                .iter()
+                .map(|(_, t)| t.description.value.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(values(&todos), vec!["a", "b", "d", "e"]);
+        assert_eq!(values(&dones), vec!["c"]);
+    }
+
+    #[test]
+    fn test_archive_options() {
+        let today = chrono::NaiveDate::from_ymd_opt(2021, 1, 10).unwrap();
+        let mut done = task("a", true);
+        assert!(ArchiveOptions::default().matches(&done, today));
+        assert!(!ArchiveOptions::default().matches(&task("b", false), today));
+
+        done.completed_date = Some(today);
+        let options = ArchiveOptions {
+            older_than: Some(1),
+        };
+        assert!(!options.matches(&done, today));
+        done.completed_date = today.pred_opt();
+        assert!(options.matches(&done, today));
+    }
+}
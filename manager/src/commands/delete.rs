@@ -1,36 +1,167 @@
-use crate::TodoManager;
+use crate::{Selection, TaskRef, TodoManager};
 use todo_txt_model::prelude::*;
 
 impl TodoManager {
-    #[tracing::instrument(parent = None, skip(self))]
-    pub fn delete(&self, task_id: usize) -> Result<Task> {
-        let tasks = self.list(
-            super::list::Filter::default(),
-            super::list::Order::default(),
-        )?;
-        let ((_, deleted), tasks) = self.take_task_by_id(task_id, tasks)?;
-
-        let (todos, dones) = self.split_tasks_todo_and_done(tasks)?;
-        crate::commands::write_tasks(&self.todo_file, &todos)?;
-        crate::commands::write_tasks(&self.done_file, &dones)?;
+    #[tracing::instrument(parent = None, skip(self, task_ref))]
+    pub fn delete(&self, task_ref: impl Into<TaskRef>) -> Result<Task> {
+        let task_ref = task_ref.into();
+        tracing::debug!("task: {}", task_ref);
+        let _lock = self.lock()?;
+        let (tasks, layout) = self.read_for_write()?;
+        let ((task_id, deleted), mut tasks) = self.take_task(&task_ref, tasks)?;
+        if self.preserve_line_numbers {
+            tasks = self.set_task_at(task_id, crate::commands::placeholder(), tasks)?;
+        }
+
+        self.save_tasks(&format!("delete {}", task_ref), tasks, &layout)?;
         Ok(deleted)
     }
 
-    #[cfg(any(feature = "rt_tokio", feature = "rt_async_std", feature = "rt_smol"))]
-    #[tracing::instrument(parent = None, skip(self))]
-    pub async fn delete_async(&self, task_id: usize) -> Result<Task> {
-        let tasks = self
-            .list_async(
-                super::list::Filter::default(),
-                super::list::Order::default(),
-            )
-            .await?;
-
-        let ((_, deleted), tasks) = self.take_task_by_id(task_id, tasks)?;
-
-        let (todos, dones) = self.split_tasks_todo_and_done(tasks)?;
-        crate::commands::write_tasks_async(&self.todo_file, &todos).await?;
-        crate::commands::write_tasks_async(&self.done_file, &dones).await?;
-        Ok(deleted)
+    #[cfg(feature = "async")]
+    pub async fn delete_async(&self, task_ref: impl Into<TaskRef>) -> Result<Task> {
+        let task_ref = task_ref.into();
+        self.unblock(move |manager| manager.delete(task_ref)).await
+    }
+
+    /// Delete every selected task in a single read-modify-write cycle and return them with
+    /// the ids they had.
+    /// note: nothing is written when the selection is empty
+    #[tracing::instrument(parent = None, skip(self, selection))]
+    pub fn delete_many(&self, selection: impl Into<Selection>) -> Result<Vec<(usize, Task)>> {
+        let selection = selection.into();
+        tracing::debug!("selection: {:?}", selection);
+        self.edit_many("delete", |tasks| {
+            delete_selected(&selection, self.preserve_line_numbers, tasks)
+        })
+    }
+
+    #[cfg(feature = "async")]
+    pub async fn delete_many_async(
+        &self,
+        selection: impl Into<Selection>,
+    ) -> Result<Vec<(usize, Task)>> {
+        let selection = selection.into();
+        self.unblock(move |manager| manager.delete_many(selection))
+            .await
+    }
+}
+
+/// note: the remaining tasks keep their original index, which `save_tasks` relies on
+/// note: with `placeholders` deleted tasks are replaced by blank lines instead of removed
+pub(crate) fn delete_selected(
+    selection: &Selection,
+    placeholders: bool,
+    tasks: &mut Vec<(usize, Task)>,
+) -> Result<Vec<(usize, Task)>> {
+    let positions = selection.positions(tasks, chrono::Utc::now().date_naive())?;
+    let mut deleted = positions
+        .into_iter()
+        .rev()
+        .map(|pos| {
+            let task = if placeholders {
+                std::mem::replace(&mut tasks[pos].1, crate::commands::placeholder())
+            } else {
+                tasks.remove(pos).1
+            };
+            (pos + 1, task)
+        })
+        .collect::<Vec<_>>();
+    deleted.reverse();
+    Ok(deleted)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::TaskSelector;
+
+    #[test]
+    fn test_delete_selected() {
+        let mut tasks = ["a", "b", "c", "d", "e"]
+            .iter()
+            .enumerate()
+            .map(|(i, line)| (i + 1, todo_txt_serializer::from_str(line).unwrap()))
+            .collect::<Vec<_>>();
+        let selection = Selection::from(vec![TaskSelector::Range(2, 3), TaskSelector::from(5)]);
+        let mut kept = tasks.clone();
+        let deleted = delete_selected(&selection, false, &mut tasks).unwrap();
+        assert_eq!(
+            deleted.iter().map(|(id, _)| *id).collect::<Vec<_>>(),
+            vec![2, 3, 5]
+        );
+        assert_eq!(
+            tasks.iter().map(|(id, _)| *id).collect::<Vec<_>>(),
+            vec![1, 4]
+        );
+
+        delete_selected(&selection, true, &mut kept).unwrap();
+        assert_eq!(kept.len(), 5);
+        assert!(crate::commands::is_placeholder(&kept[1].1));
+        let deleted = delete_selected(&TaskSelector::Range(1, 5).into(), true, &mut kept).unwrap();
+        assert_eq!(
+            deleted.iter().map(|(id, _)| *id).collect::<Vec<_>>(),
+            vec![1, 4]
+        );
+        assert!(matches!(
+            delete_selected(&2.into(), true, &mut kept),
+            Err(TodoTxtRsError::NotFound)
+        ));
+    }
+
+    #[test]
+    fn test_delete_with_preserved_line_numbers() {
+        use crate::{Filter, MemoryStorage, Order, Storage};
+        let storage = MemoryStorage::new();
+        let manager = crate::TodoManagerBuilder::new()
+            .with_env(false)
+            .build()
+            .unwrap()
+            .with_storage(storage.clone())
+            .with_preserve_line_numbers(true);
+        let lines = |lines: &[&str]| lines.iter().map(|l| l.to_string()).collect::<Vec<_>>();
+        storage
+            .save(&[("todo.txt".to_string(), lines(&["a", "b", "c"]))])
+            .unwrap();
+
+        manager.delete(2).unwrap();
+        manager.update_state(3, true).unwrap();
+        assert!(matches!(manager.delete(2), Err(TodoTxtRsError::NotFound)));
+        let ids = manager
+            .list(Filter::default(), Order::default())
+            .unwrap()
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![1, 3]);
+        let todo = storage.load("todo.txt").unwrap();
+        assert_eq!(todo[1], "");
+        assert!(todo[2].starts_with("x "));
+
+        assert_eq!(manager.archive(Default::default()).unwrap().len(), 1);
+        assert_eq!(storage.load("todo.txt").unwrap(), lines(&["a"]));
+    }
+
+    #[test]
+    fn test_delete_keeps_unparsed_and_blank_lines() {
+        use crate::{MemoryStorage, Storage};
+        let storage = MemoryStorage::new();
+        let manager = crate::TodoManagerBuilder::new()
+            .with_env(false)
+            .build()
+            .unwrap()
+            .with_storage(storage.clone())
+            .with_lenient(true);
+        let lines = |lines: &[&str]| lines.iter().map(|l| l.to_string()).collect::<Vec<_>>();
+        storage
+            .save(&[("todo.txt".to_string(), lines(&["a", "+bad", "b", "", "c"]))])
+            .unwrap();
+
+        manager.delete(1).unwrap();
+        assert_eq!(
+            storage.load("todo.txt").unwrap(),
+            lines(&["+bad", "b", "", "c"])
+        );
+        manager.update_state(1, true).unwrap();
+        assert_eq!(storage.load("todo.txt").unwrap(), lines(&["+bad", "", "c"]));
     }
 }
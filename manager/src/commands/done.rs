@@ -1,60 +1,160 @@
-use crate::TodoManager;
+use crate::{Selection, TaskRef, TodoManager};
 use todo_txt_model::prelude::*;
 
 impl TodoManager {
-    #[tracing::instrument(parent = None, skip(self))]
-    pub fn update_state(&self, task_id: usize, done: bool) -> Result<(usize, Task)> {
-        let tasks = self.list(
-            super::list::Filter::default(),
-            super::list::Order::default(),
+    #[tracing::instrument(parent = None, skip(self, task_ref))]
+    pub fn update_state(&self, task_ref: impl Into<TaskRef>, done: bool) -> Result<(usize, Task)> {
+        let task_ref = task_ref.into();
+        tracing::debug!("task: {}", task_ref);
+        let _lock = self.lock()?;
+        let (tasks, layout) = self.read_for_write()?;
+
+        let ((task_id, mut task), tasks) = self.take_task(&task_ref, tasks)?;
+        let was_done = task.is_done();
+        set_state(&mut task, done);
+
+        let mut tasks = self.set_task_at(task_id, task.clone(), tasks)?;
+        if done && !was_done {
+            self.push_next_occurrence(&task, &mut tasks);
+        }
+        self.save_tasks(
+            &format!("{} {}", if done { "done" } else { "undone" }, task_ref),
+            tasks,
+            &layout,
         )?;
+        Ok((task_id, task))
+    }
 
-        let ((task_id, mut task), tasks) = self.take_task_by_id(task_id, tasks)?;
+    #[cfg(feature = "async")]
+    pub async fn update_state_async(
+        &self,
+        task_ref: impl Into<TaskRef>,
+        done: bool,
+    ) -> Result<(usize, Task)> {
+        let task_ref = task_ref.into();
+        self.unblock(move |manager| manager.update_state(task_ref, done))
+            .await
+    }
 
-        if done {
-            task.state = TaskState::Done;
-            task.completed_date = Some(chrono::Utc::now().date_naive());
-        } else {
-            task.state = TaskState::Todo;
-            if task.created_date.is_none() {
-                task.created_date = task.completed_date;
+    /// Like `update_state` for every selected task, in a single read-modify-write cycle.
+    /// note: tasks already in the target state keep their dates and are not returned, nothing
+    /// is written when no task changes
+    #[tracing::instrument(parent = None, skip(self, selection))]
+    pub fn update_state_many(
+        &self,
+        selection: impl Into<Selection>,
+        done: bool,
+    ) -> Result<Vec<(usize, Task)>> {
+        let selection = selection.into();
+        tracing::debug!("selection: {:?}", selection);
+        self.edit_many(if done { "done" } else { "undone" }, |tasks| {
+            self.update_state_selected(&selection, done, tasks)
+        })
+    }
+
+    #[cfg(feature = "async")]
+    pub async fn update_state_many_async(
+        &self,
+        selection: impl Into<Selection>,
+        done: bool,
+    ) -> Result<Vec<(usize, Task)>> {
+        let selection = selection.into();
+        self.unblock(move |manager| manager.update_state_many(selection, done))
+            .await
+    }
+}
+
+impl TodoManager {
+    /// Add the next occurrence of a recurring task that was just completed.
+    /// note: the next occurrence gets a new uid when the completed task has one
+    pub(self) fn push_next_occurrence(&self, task: &Task, tasks: &mut Vec<(usize, Task)>) {
+        let completed = task
+            .completed_date
+            .unwrap_or_else(|| chrono::Utc::now().date_naive());
+        if let Some(mut next) = task.next_occurrence(completed) {
+            if next.uid().is_some() {
+                let uid = crate::task_ref::generate_uid(tasks.iter().map(|(_, t)| t));
+                next.set_uid(Some(uid));
             }
-            task.completed_date = None;
+            tracing::info!("next occurrence: {:?}", next);
+            tasks.push((tasks.len() + 1, next));
         }
-
-        let tasks = self.set_task_at(task_id, task.clone(), tasks)?;
-        let (todos, dones) = self.split_tasks_todo_and_done(tasks)?;
-        crate::commands::write_tasks(&self.todo_file, &todos)?;
-        crate::commands::write_tasks(&self.done_file, &dones)?;
-        Ok((task_id, task))
     }
 
-    #[cfg(any(feature = "rt_tokio", feature = "rt_async_std", feature = "rt_smol"))]
-    #[tracing::instrument(parent = None, skip(self))]
-    pub async fn update_state_async(&self, task_id: usize, done: bool) -> Result<(usize, Task)> {
-        let tasks = self
-            .list_async(
-                super::list::Filter::default(),
-                super::list::Order::default(),
-            )
-            .await?;
-        let ((task_id, mut task), tasks) = self.take_task_by_id(task_id, tasks)?;
-
-        if done {
-            task.state = TaskState::Done;
-            task.completed_date = Some(chrono::Utc::now().date_naive());
-        } else {
-            task.state = TaskState::Todo;
-            if task.created_date.is_none() {
-                task.created_date = task.completed_date;
+    /// Update the selected tasks in place and return them with their ids.
+    /// note: tasks already in the target state are skipped
+    /// note: next occurrences of recurring tasks are appended after all of them are updated
+    pub(crate) fn update_state_selected(
+        &self,
+        selection: &Selection,
+        done: bool,
+        tasks: &mut Vec<(usize, Task)>,
+    ) -> Result<Vec<(usize, Task)>> {
+        let positions = selection.positions(tasks, chrono::Utc::now().date_naive())?;
+        let mut updated = Vec::with_capacity(positions.len());
+        let mut completed = Vec::new();
+        for pos in positions {
+            let task = &mut tasks[pos].1;
+            if task.is_done() == done {
+                continue;
+            }
+            set_state(task, done);
+            if done {
+                completed.push(task.clone());
             }
-            task.completed_date = None;
+            updated.push((pos + 1, task.clone()));
+        }
+        for task in &completed {
+            self.push_next_occurrence(task, tasks);
         }
+        Ok(updated)
+    }
+}
 
-        let tasks = self.set_task_at(task_id, task.clone(), tasks)?;
-        let (todos, dones) = self.split_tasks_todo_and_done(tasks)?;
-        crate::commands::write_tasks_async(&self.todo_file, &todos).await?;
-        crate::commands::write_tasks_async(&self.done_file, &dones).await?;
-        Ok((task_id, task))
+fn set_state(task: &mut Task, done: bool) {
+    if done {
+        task.state = TaskState::Done;
+        task.completed_date = Some(chrono::Utc::now().date_naive());
+    } else {
+        task.state = TaskState::Todo;
+        if task.created_date.is_none() {
+            task.created_date = task.completed_date;
+        }
+        task.completed_date = None;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Filter, MemoryStorage, Storage, TodoManagerBuilder};
+
+    #[test]
+    fn test_update_state_many_skips_tasks_in_target_state() {
+        let storage = MemoryStorage::new();
+        let manager = TodoManagerBuilder::new()
+            .with_env(false)
+            .build()
+            .unwrap()
+            .with_storage(storage.clone());
+        let old = "x 2020-01-05 2020-01-01 old +acme";
+        storage
+            .save(&[
+                ("todo.txt".to_string(), vec!["new +acme".to_string()]),
+                ("done.txt".to_string(), vec![old.to_string()]),
+            ])
+            .unwrap();
+        let filter = Filter {
+            project: Some(vec!["acme".to_string()]),
+            ..Default::default()
+        };
+        let updated = manager.update_state_many(filter.clone(), true).unwrap();
+        assert_eq!(updated.len(), 1);
+        assert_eq!(updated[0].1.description.value, "new");
+        let done = storage.load("done.txt").unwrap();
+        assert_eq!(done.len(), 2);
+        assert!(done.contains(&old.to_string()));
+
+        assert_eq!(manager.update_state_many(filter, true).unwrap(), Vec::new());
+        assert_eq!(storage.load("done.txt").unwrap(), done);
     }
 }
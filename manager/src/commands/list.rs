@@ -1,12 +1,104 @@
-use crate::TodoManager;
+use crate::commands::FileLayout;
+use crate::{Diagnostic, Query, TaskRef, TextSearch, TodoManager};
+use std::cmp::Ordering;
 use todo_txt_model::prelude::*;
 
 #[derive(Debug, Clone, Default)]
 pub struct Filter {
     pub state: Option<TaskState>,
-    pub priority: Option<Vec<TaskPriority>>,
+    /// Keep only tasks matching any of these.
+    pub priority: Option<Vec<PriorityFilter>>,
     pub project: Option<Vec<String>>,
     pub context: Option<Vec<String>>,
+    /// Keep only tasks whose urgency is one of these.
+    pub urgency: Option<Vec<TaskUrgency>>,
+    /// Hide tasks whose threshold date is in the future.
+    pub hide_future: bool,
+    /// Keep only tasks matching this expression.
+    pub query: Option<Query>,
+    /// Keep only tasks whose text matches.
+    pub text: Option<TextSearch>,
+}
+
+impl Filter {
+    /// Whether `task` passes every condition of the filter.
+    /// note: shared by the parallel and the sequential paths of `list`
+    /// note: the blank lines kept by `with_preserve_line_numbers` never match
+    pub fn matches(&self, task: &Task, today: chrono::NaiveDate) -> bool {
+        !crate::commands::is_placeholder(task)
+            && self.state.is_none_or(|f| task.state == f)
+            && self
+                .priority
+                .as_ref()
+                .is_none_or(|filter| filter.iter().any(|p| p.matches(task.priority)))
+            && self
+                .project
+                .as_ref()
+                .is_none_or(|filter| filter.iter().all(|p| task.description.project.contains(p)))
+            && self
+                .context
+                .as_ref()
+                .is_none_or(|filter| filter.iter().all(|c| task.description.context.contains(c)))
+            && self
+                .urgency
+                .as_ref()
+                .is_none_or(|filter| filter.contains(&task.urgency(today)))
+            && (!self.hide_future || task.is_visible(today))
+            && self.query.as_ref().is_none_or(|q| q.matches(task, today))
+            && self.text.as_ref().is_none_or(|s| s.matches(task))
+    }
+}
+
+/// Priority condition: `A`, a range `A..C`, `any` or `none`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriorityFilter {
+    Is(TaskPriority),
+    /// Both ends included, in either order.
+    Range(TaskPriority, TaskPriority),
+    /// Any priority is set.
+    Any,
+    /// No priority is set.
+    None,
+}
+
+impl PriorityFilter {
+    pub fn matches(&self, priority: Option<TaskPriority>) -> bool {
+        match (*self, priority) {
+            (Self::Any, p) => p.is_some(),
+            (Self::None, p) => p.is_none(),
+            (_, None) => false,
+            (Self::Is(f), Some(p)) => f == p,
+            (Self::Range(a, b), Some(p)) => a.min(b) <= p && p <= a.max(b),
+        }
+    }
+}
+
+impl From<TaskPriority> for PriorityFilter {
+    fn from(value: TaskPriority) -> Self {
+        Self::Is(value)
+    }
+}
+
+impl std::str::FromStr for PriorityFilter {
+    type Err = TodoTxtRsError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let priority = |s: &str| {
+            let mut chars = s.trim().chars();
+            match (chars.next(), chars.next()) {
+                (Some(p), None) if p.is_ascii_alphabetic() => Ok(TaskPriority::from(p)),
+                _ => Err(TodoTxtRsError::InvalidPriority(s.to_string())),
+            }
+        };
+        match s.trim().to_ascii_lowercase().as_str() {
+            "any" => Ok(Self::Any),
+            "none" => Ok(Self::None),
+            _ => match s.split_once("..") {
+                Some((a, b)) => Ok(Self::Range(priority(a)?, priority(b)?)),
+                None => Ok(Self::Is(priority(s)?)),
+            },
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
@@ -16,56 +108,204 @@ pub enum OrderOption {
     Priority,
     CompletedDate,
     CreatedDate,
+    DueDate,
+    /// Description text, ignoring case
+    Description,
+    /// First project
+    Project,
+    /// First context
+    Context,
+    /// Position in todo.txt and done.txt
+    Position,
+}
+
+impl OrderOption {
+    /// Newest first for completed and created dates, ascending otherwise.
+    pub fn default_direction(&self) -> SortDirection {
+        match self {
+            Self::CompletedDate | Self::CreatedDate => SortDirection::Desc,
+            _ => SortDirection::Asc,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+#[clap(rename_all = "snake_case")]
+pub enum SortDirection {
+    Asc,
+    Desc,
+}
+
+/// Where tasks without the sorted value go, regardless of the direction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+#[clap(rename_all = "snake_case")]
+pub enum NullsOrder {
+    First,
+    #[default]
+    Last,
+}
+
+/// A sort key written as `key[:asc|:desc][:nulls_first|:nulls_last]`, e.g. `due_date:desc`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortKey {
+    pub by: OrderOption,
+    /// note: None means `OrderOption::default_direction`
+    pub direction: Option<SortDirection>,
+    pub nulls: NullsOrder,
+}
+
+impl From<OrderOption> for SortKey {
+    fn from(by: OrderOption) -> Self {
+        Self {
+            by,
+            direction: None,
+            nulls: NullsOrder::default(),
+        }
+    }
+}
+
+impl std::str::FromStr for SortKey {
+    type Err = TodoTxtRsError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        use clap::ValueEnum;
+        let invalid = || TodoTxtRsError::InvalidSortKey(s.to_string());
+        let mut parts = s.split(':');
+        let by = OrderOption::from_str(parts.next().unwrap_or_default().trim(), true)
+            .map_err(|_| invalid())?;
+        let mut key = Self::from(by);
+        for part in parts {
+            match part.trim().to_ascii_lowercase().as_str() {
+                "asc" => key.direction = Some(SortDirection::Asc),
+                "desc" => key.direction = Some(SortDirection::Desc),
+                "nulls_first" => key.nulls = NullsOrder::First,
+                "nulls_last" => key.nulls = NullsOrder::Last,
+                _ => return Err(invalid()),
+            }
+        }
+        Ok(key)
+    }
 }
+
+impl SortKey {
+    fn compare(&self, (a_idx, a): &(usize, Task), (b_idx, b): &(usize, Task)) -> Ordering {
+        match self.by {
+            OrderOption::State => self.compare_values(Some(a.state), Some(b.state)),
+            OrderOption::Priority => self.compare_values(a.priority, b.priority),
+            OrderOption::CompletedDate => self.compare_values(a.completed_date, b.completed_date),
+            OrderOption::CreatedDate => self.compare_values(a.created_date, b.created_date),
+            OrderOption::DueDate => self.compare_values(a.due_date(), b.due_date()),
+            OrderOption::Description => self.compare_values(
+                Some(a.description.value.to_lowercase()),
+                Some(b.description.value.to_lowercase()),
+            ),
+            OrderOption::Project => {
+                self.compare_values(a.description.project.first(), b.description.project.first())
+            }
+            OrderOption::Context => {
+                self.compare_values(a.description.context.first(), b.description.context.first())
+            }
+            OrderOption::Position => self.compare_values(Some(a_idx), Some(b_idx)),
+        }
+    }
+
+    fn compare_values<T: Ord>(&self, a: Option<T>, b: Option<T>) -> Ordering {
+        let direction = self
+            .direction
+            .unwrap_or_else(|| self.by.default_direction());
+        match (a, b, self.nulls) {
+            (Some(a), Some(b), _) => match direction {
+                SortDirection::Asc => a.cmp(&b),
+                SortDirection::Desc => b.cmp(&a),
+            },
+            (None, None, _) => Ordering::Equal,
+            (None, Some(_), NullsOrder::First) | (Some(_), None, NullsOrder::Last) => {
+                Ordering::Less
+            }
+            (None, Some(_), NullsOrder::Last) | (Some(_), None, NullsOrder::First) => {
+                Ordering::Greater
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct Order {
-    pub by: Option<Vec<OrderOption>>,
+    /// Sort keys, the one on the left has the highest priority.
+    pub by: Option<Vec<SortKey>>,
+    /// Reverse the whole list after sorting.
     pub reverse: bool,
 }
 
+/// Listed tasks with their index, and the lines that were skipped.
+pub type ListWithDiagnostics = (Vec<(usize, Task)>, Vec<Diagnostic>);
+
 impl TodoManager {
     #[tracing::instrument(parent = None, skip(self))]
     pub fn list(&self, filter: Filter, order: Order) -> Result<Vec<(usize, Task)>> {
-        let tasks = {
-            let mut todos = crate::commands::read_tasks_from_file(&self.todo_file)?;
-            let dones = crate::commands::read_tasks_from_file(&self.done_file)?;
-            todos.extend(dones);
-            todos
-        };
-        tracing::debug!("tasks before filter: {:?}", tasks);
-        let task_enum = {
-            #[cfg(feature = "parallel")]
-            {
-                use rayon::prelude::*;
-                tasks
-                    .into_par_iter()
-                    .enumerate()
-                    .map(|(idx, t)| (idx + 1, t))
-                    .collect()
-            }
-            #[cfg(not(feature = "parallel"))]
-            {
-                tasks
-                    .into_iter()
-                    .enumerate()
-                    .map(|(idx, t)| (idx + 1, t))
-                    .collect()
-            }
-        };
-        Ok(self.sort_tasks(self.filter_tasks(task_enum, filter), order))
+        if self.storage.needs_recovery() {
+            // finish the interrupted write under the lock
+            drop(self.lock()?);
+        }
+        let tasks = self.read_all_tasks()?;
+        Ok(self.sort_tasks(self.filter_tasks(tasks, filter), order))
     }
 
-    #[cfg(any(feature = "rt_async_std", feature = "rt_tokio", feature = "rt_smol"))]
+    /// List tasks, skipping the lines that can not be parsed and reporting them.
+    /// note: this reads leniently regardless of `with_lenient`
     #[tracing::instrument(parent = None, skip(self))]
-    pub async fn list_async(&self, filter: Filter, order: Order) -> Result<Vec<(usize, Task)>> {
-        let tasks = {
-            let mut todos = crate::commands::read_tasks_from_file_async(&self.todo_file).await?;
-            let dones = crate::commands::read_tasks_from_file_async(&self.done_file).await?;
+    pub fn list_with_diagnostics(
+        &self,
+        filter: Filter,
+        order: Order,
+    ) -> Result<ListWithDiagnostics> {
+        if self.storage.needs_recovery() {
+            // finish the interrupted write under the lock
+            drop(self.lock()?);
+        }
+        let (tasks, diagnostics) = self.read_all_tasks_with_diagnostics(true)?;
+        Ok((
+            self.sort_tasks(self.filter_tasks(tasks, filter), order),
+            diagnostics,
+        ))
+    }
+
+    /// Read todo.txt and done.txt, numbering the tasks from 1.
+    /// note: in lenient mode the lines that can not be parsed are skipped
+    pub(crate) fn read_all_tasks(&self) -> Result<Vec<(usize, Task)>> {
+        Ok(self.read_files(self.lenient)?.0)
+    }
+
+    /// Like `read_all_tasks`, with the layout of the files for `save_tasks`.
+    /// note: call it under the lock, the layout is only valid until the files change
+    pub(crate) fn read_for_write(&self) -> Result<(Vec<(usize, Task)>, FileLayout)> {
+        let (tasks, _, layout) = self.read_files(self.lenient)?;
+        Ok((tasks, layout))
+    }
+
+    fn read_all_tasks_with_diagnostics(&self, lenient: bool) -> Result<ListWithDiagnostics> {
+        let (tasks, diagnostics, _) = self.read_files(lenient)?;
+        Ok((tasks, diagnostics))
+    }
+
+    #[tracing::instrument(parent = None, skip(self))]
+    fn read_files(&self, lenient: bool) -> Result<crate::ReadFilesResult> {
+        let (tasks, diagnostics, layout) = {
+            let (mut todos, mut diagnostics, todo_kept) =
+                self.read_tasks(&self.todo_file, lenient)?;
+            let (dones, done_diagnostics, done_kept) = self.read_tasks(&self.done_file, lenient)?;
+            let layout = FileLayout {
+                todo_len: todos.len(),
+                done_len: dones.len(),
+                todo_kept,
+                done_kept,
+            };
             todos.extend(dones);
-            todos
+            diagnostics.extend(done_diagnostics);
+            (todos, diagnostics, layout)
         };
         tracing::debug!("tasks before filter: {:?}", tasks);
-        let task_enum = {
+        let tasks = {
             #[cfg(feature = "parallel")]
             {
                 use rayon::prelude::*;
@@ -84,84 +324,279 @@ impl TodoManager {
                     .collect()
             }
         };
-        Ok(self.sort_tasks(self.filter_tasks(task_enum, filter), order))
+        Ok((tasks, diagnostics, layout))
+    }
+
+    #[cfg(feature = "async")]
+    pub async fn list_async(&self, filter: Filter, order: Order) -> Result<Vec<(usize, Task)>> {
+        self.unblock(move |manager| manager.list(filter, order))
+            .await
+    }
+
+    #[cfg(feature = "async")]
+    pub async fn list_with_diagnostics_async(
+        &self,
+        filter: Filter,
+        order: Order,
+    ) -> Result<ListWithDiagnostics> {
+        self.unblock(move |manager| manager.list_with_diagnostics(filter, order))
+            .await
     }
 }
 
+impl TodoManager {
+    /// Get a single task with its current index.
+    #[tracing::instrument(parent = None, skip(self, task_ref))]
+    pub fn get(&self, task_ref: impl Into<TaskRef>) -> Result<(usize, Task)> {
+        let task_ref = task_ref.into();
+        tracing::debug!("task: {}", task_ref);
+        if self.storage.needs_recovery() {
+            // finish the interrupted write under the lock
+            drop(self.lock()?);
+        }
+        let tasks = self.read_all_tasks()?;
+        let (task, _) = self.take_task(&task_ref, tasks)?;
+        Ok(task)
+    }
+
+    #[cfg(feature = "async")]
+    pub async fn get_async(&self, task_ref: impl Into<TaskRef>) -> Result<(usize, Task)> {
+        let task_ref = task_ref.into();
+        self.unblock(move |manager| manager.get(task_ref)).await
+    }
+}
+
+fn filter_tasks(
+    tasks: Vec<(usize, Task)>,
+    filter: &Filter,
+    today: chrono::NaiveDate,
+) -> Vec<(usize, Task)> {
+    #[cfg(feature = "parallel")]
+    {
+        filter_tasks_parallel(tasks, filter, today)
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        filter_tasks_sequential(tasks, filter, today)
+    }
+}
+
+#[cfg(feature = "parallel")]
+fn filter_tasks_parallel(
+    tasks: Vec<(usize, Task)>,
+    filter: &Filter,
+    today: chrono::NaiveDate,
+) -> Vec<(usize, Task)> {
+    use rayon::prelude::*;
+    tasks
+        .into_par_iter()
+        .filter(|(_, t)| filter.matches(t, today))
+        .collect()
+}
+
+/// note: also built in tests of parallel builds, to compare both paths
+#[cfg(any(test, not(feature = "parallel")))]
+fn filter_tasks_sequential(
+    tasks: Vec<(usize, Task)>,
+    filter: &Filter,
+    today: chrono::NaiveDate,
+) -> Vec<(usize, Task)> {
+    tasks
+        .into_iter()
+        .filter(|(_, t)| filter.matches(t, today))
+        .collect()
+}
+
 impl TodoManager {
     #[tracing::instrument(parent = None, skip(self, tasks))]
     fn filter_tasks(&self, tasks: Vec<(usize, Task)>, filter: Filter) -> Vec<(usize, Task)> {
+        filter_tasks(tasks, &filter, chrono::Utc::now().date_naive())
+    }
+
+    #[tracing::instrument(parent = None, skip(self, tasks))]
+    fn sort_tasks(&self, tasks: Vec<(usize, Task)>, order: Order) -> Vec<(usize, Task)> {
+        sort_tasks(tasks, &order)
+    }
+}
+
+fn sort_tasks(mut tasks: Vec<(usize, Task)>, order: &Order) -> Vec<(usize, Task)> {
+    #[cfg(feature = "parallel")]
+    use rayon::prelude::*;
+    if let Some(keys) = &order.by {
+        let compare = |a: &(usize, Task), b: &(usize, Task)| {
+            keys.iter()
+                .fold(Ordering::Equal, |o, key| o.then_with(|| key.compare(a, b)))
+        };
         #[cfg(feature = "parallel")]
-        {
-            use rayon::prelude::*;
-            tasks
-                .into_par_iter()
-                .filter(|(_, t)| {
-                    filter.state.is_none_or(|f| t.state == f)
-                        && filter
-                            .priority
-                            .as_ref()
-                            .is_none_or(|filter| t.priority.is_some_and(|t| filter.contains(&t)))
-                        && filter.project.as_ref().is_none_or(|filter| {
-                            filter.par_iter().all(|p| t.description.project.contains(p))
-                        })
-                        && filter.context.as_ref().is_none_or(|filter| {
-                            filter.par_iter().all(|c| t.description.context.contains(c))
-                        })
-                })
-                .collect()
-        }
+        tasks.par_sort_by(compare);
         #[cfg(not(feature = "parallel"))]
-        {
-            tasks
-                .into_iter()
-                .filter(|(_, t)| {
-                    filter.state.is_none_or(|f| t.state == f)
-                        && filter.project.as_ref().is_none_or(|filter| {
-                            filter.iter().all(|p| t.description.project.contains(p))
-                        })
-                        && filter.context.as_ref().is_none_or(|filter| {
-                            filter.iter().all(|c| t.description.context.contains(c))
-                        })
-                })
-                .collect()
+        tasks.sort_by(compare);
+    }
+    if order.reverse {
+        tasks.reverse();
+    }
+    tasks
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn filter_suite_tasks() -> Vec<(usize, Task)> {
+        [
+            "(A) pay rent +home",
+            "(B) call mom @phone +home",
+            "(D) fix bike",
+            "no priority +home",
+            "x (A) done task",
+        ]
+        .iter()
+        .enumerate()
+        .map(|(idx, line)| (idx + 1, todo_txt_serializer::from_str(line).unwrap()))
+        .collect()
+    }
+
+    fn today() -> chrono::NaiveDate {
+        chrono::NaiveDate::from_ymd_opt(2021, 1, 10).unwrap()
+    }
+
+    fn filtered(filter: Filter) -> Vec<usize> {
+        filter_tasks(filter_suite_tasks(), &filter, today())
+            .into_iter()
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    fn priority(filters: &[&str]) -> Filter {
+        Filter {
+            priority: Some(filters.iter().map(|f| f.parse().unwrap()).collect()),
+            ..Default::default()
         }
     }
 
-    #[tracing::instrument(parent = None, skip(self, tasks))]
-    fn sort_tasks(&self, mut tasks: Vec<(usize, Task)>, order: Order) -> Vec<(usize, Task)> {
-        #[cfg(feature = "parallel")]
-        use rayon::prelude::*;
-        if let Some(mut orderlist) = order.by {
-            orderlist.reverse();
-            fn compare_by(
-                (_, a): &(usize, Task),
-                (_, b): &(usize, Task),
-                by: OrderOption,
-            ) -> std::cmp::Ordering {
-                match by {
-                    OrderOption::State => a.state.cmp(&b.state),
-                    OrderOption::Priority => a.priority.cmp(&b.priority),
-                    OrderOption::CompletedDate => b.completed_date.cmp(&a.completed_date),
-                    OrderOption::CreatedDate => b.created_date.cmp(&a.created_date),
-                }
-            }
-            for by in orderlist {
-                #[cfg(feature = "parallel")]
-                {
-                    tasks.par_sort_by(|a, b| compare_by(a, b, by));
-                }
-                #[cfg(not(feature = "parallel"))]
-                {
-                    tasks.sort_by(|a, b| compare_by(a, b, by));
-                }
-            }
-            if order.reverse {
-                tasks.reverse();
-            }
-            tasks
-        } else {
-            tasks
+    #[test]
+    fn test_parse_priority_filter() {
+        assert_eq!(
+            "a".parse::<PriorityFilter>().unwrap(),
+            PriorityFilter::Is(TaskPriority::A)
+        );
+        assert_eq!(
+            "C..A".parse::<PriorityFilter>().unwrap(),
+            PriorityFilter::Range(TaskPriority::C, TaskPriority::A)
+        );
+        assert_eq!(
+            "Any".parse::<PriorityFilter>().unwrap(),
+            PriorityFilter::Any
+        );
+        assert_eq!(
+            "none".parse::<PriorityFilter>().unwrap(),
+            PriorityFilter::None
+        );
+        assert!("AB".parse::<PriorityFilter>().is_err());
+        assert!("A..".parse::<PriorityFilter>().is_err());
+        assert!("1".parse::<PriorityFilter>().is_err());
+    }
+
+    #[test]
+    fn test_filter_priority() {
+        assert_eq!(filtered(priority(&["A"])), vec![1, 5]);
+        assert_eq!(filtered(priority(&["A..C"])), vec![1, 2, 5]);
+        assert_eq!(filtered(priority(&["C..A"])), vec![1, 2, 5]);
+        assert_eq!(filtered(priority(&["any"])), vec![1, 2, 3, 5]);
+        assert_eq!(filtered(priority(&["none"])), vec![4]);
+        assert_eq!(filtered(priority(&["D", "none"])), vec![3, 4]);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_filter_parallel_matches_sequential() {
+        // enough tasks for rayon to split the work
+        let tasks = (0..200)
+            .flat_map(|_| filter_suite_tasks())
+            .enumerate()
+            .map(|(idx, (_, task))| (idx + 1, task))
+            .collect::<Vec<_>>();
+        for filters in [
+            &["A"][..],
+            &["A..C"],
+            &["C..A"],
+            &["any"],
+            &["none"],
+            &["D", "none"],
+        ] {
+            let filter = priority(filters);
+            let sequential = filter_tasks_sequential(tasks.clone(), &filter, today());
+            let parallel = filter_tasks_parallel(tasks.clone(), &filter, today());
+            assert!(!sequential.is_empty());
+            assert_eq!(parallel, sequential, "filter {:?}", filters);
         }
     }
+
+    #[test]
+    fn test_filter_combined() {
+        assert_eq!(filtered(Filter::default()), vec![1, 2, 3, 4, 5]);
+        let filter = Filter {
+            state: Some(TaskState::Todo),
+            project: Some(vec!["home".to_string()]),
+            ..priority(&["any"])
+        };
+        assert_eq!(filtered(filter), vec![1, 2]);
+        let filter = Filter {
+            context: Some(vec!["phone".to_string()]),
+            ..Default::default()
+        };
+        assert_eq!(filtered(filter), vec![2]);
+    }
+
+    fn sorted(keys: &[&str]) -> Vec<usize> {
+        let tasks = [
+            "(B) b task +work due:2021-01-05",
+            "a task @phone",
+            "(A) C task +home due:2021-01-03",
+            "x (C) 2021-01-01 2020-12-01 done task +home",
+        ]
+        .iter()
+        .enumerate()
+        .map(|(idx, line)| (idx + 1, todo_txt_serializer::from_str(line).unwrap()))
+        .collect();
+        let order = Order {
+            by: Some(keys.iter().map(|k| k.parse().unwrap()).collect()),
+            reverse: false,
+        };
+        sort_tasks(tasks, &order)
+            .into_iter()
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_sort_key() {
+        assert_eq!(
+            "due_date:desc:nulls_first".parse::<SortKey>().unwrap(),
+            SortKey {
+                by: OrderOption::DueDate,
+                direction: Some(SortDirection::Desc),
+                nulls: NullsOrder::First,
+            }
+        );
+        assert_eq!(
+            "Priority".parse::<SortKey>().unwrap(),
+            SortKey::from(OrderOption::Priority)
+        );
+        assert!("priority:up".parse::<SortKey>().is_err());
+        assert!("size".parse::<SortKey>().is_err());
+    }
+
+    #[test]
+    fn test_sort_tasks() {
+        // tasks without a priority go last
+        assert_eq!(sorted(&["priority"]), vec![3, 1, 4, 2]);
+        assert_eq!(sorted(&["priority:nulls_first"]), vec![2, 3, 1, 4]);
+        assert_eq!(sorted(&["priority:desc"]), vec![4, 1, 3, 2]);
+        assert_eq!(sorted(&["due_date"]), vec![3, 1, 2, 4]);
+        assert_eq!(sorted(&["description"]), vec![2, 1, 3, 4]);
+        assert_eq!(sorted(&["project", "position:desc"]), vec![4, 3, 1, 2]);
+        assert_eq!(sorted(&["context", "position"]), vec![2, 1, 3, 4]);
+        assert_eq!(sorted(&["state", "position:desc"]), vec![3, 2, 1, 4]);
+    }
 }
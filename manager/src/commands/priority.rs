@@ -1,50 +1,76 @@
-use crate::TodoManager;
+use crate::{Selection, TaskRef, TodoManager};
 use todo_txt_model::prelude::*;
 
 impl TodoManager {
-    #[tracing::instrument(parent = None, skip(self))]
+    #[tracing::instrument(parent = None, skip(self, task_ref))]
     pub fn set_priority(
         &self,
-        task_id: usize,
+        task_ref: impl Into<TaskRef>,
         priority: Option<TaskPriority>,
     ) -> Result<(usize, Task)> {
-        let tasks = self.list(
-            super::list::Filter::default(),
-            super::list::Order::default(),
-        )?;
+        let task_ref = task_ref.into();
+        tracing::debug!("task: {}", task_ref);
+        let _lock = self.lock()?;
+        let (tasks, layout) = self.read_for_write()?;
 
-        let ((task_id, mut task), tasks) = self.take_task_by_id(task_id, tasks)?;
+        let ((task_id, mut task), tasks) = self.take_task(&task_ref, tasks)?;
 
         task.priority = priority;
 
         let tasks = self.set_task_at(task_id, task.clone(), tasks)?;
-        let (todos, dones) = self.split_tasks_todo_and_done(tasks)?;
-        crate::commands::write_tasks(&self.todo_file, &todos)?;
-        crate::commands::write_tasks(&self.done_file, &dones)?;
+        self.save_tasks(&format!("priority {}", task_ref), tasks, &layout)?;
         Ok((task_id, task))
     }
 
-    #[cfg(any(feature = "rt_tokio", feature = "rt_async_std", feature = "rt_smol"))]
-    #[tracing::instrument(parent = None, skip(self))]
+    #[cfg(feature = "async")]
     pub async fn set_priority_async(
         &self,
-        task_id: usize,
+        task_ref: impl Into<TaskRef>,
         priority: Option<TaskPriority>,
     ) -> Result<(usize, Task)> {
-        let tasks = self
-            .list_async(
-                super::list::Filter::default(),
-                super::list::Order::default(),
-            )
-            .await?;
-        let ((task_id, mut task), tasks) = self.take_task_by_id(task_id, tasks)?;
+        let task_ref = task_ref.into();
+        self.unblock(move |manager| manager.set_priority(task_ref, priority))
+            .await
+    }
 
-        task.priority = priority;
+    /// Like `set_priority` for every selected task, in a single read-modify-write cycle.
+    /// note: nothing is written when the selection is empty
+    #[tracing::instrument(parent = None, skip(self, selection))]
+    pub fn set_priority_many(
+        &self,
+        selection: impl Into<Selection>,
+        priority: Option<TaskPriority>,
+    ) -> Result<Vec<(usize, Task)>> {
+        let selection = selection.into();
+        tracing::debug!("selection: {:?}", selection);
+        self.edit_many("priority", |tasks| {
+            set_priority_selected(&selection, priority, tasks)
+        })
+    }
 
-        let tasks = self.set_task_at(task_id, task.clone(), tasks)?;
-        let (todos, dones) = self.split_tasks_todo_and_done(tasks)?;
-        crate::commands::write_tasks_async(&self.todo_file, &todos).await?;
-        crate::commands::write_tasks_async(&self.done_file, &dones).await?;
-        Ok((task_id, task))
+    #[cfg(feature = "async")]
+    pub async fn set_priority_many_async(
+        &self,
+        selection: impl Into<Selection>,
+        priority: Option<TaskPriority>,
+    ) -> Result<Vec<(usize, Task)>> {
+        let selection = selection.into();
+        self.unblock(move |manager| manager.set_priority_many(selection, priority))
+            .await
     }
 }
+
+pub(crate) fn set_priority_selected(
+    selection: &Selection,
+    priority: Option<TaskPriority>,
+    tasks: &mut [(usize, Task)],
+) -> Result<Vec<(usize, Task)>> {
+    let positions = selection.positions(tasks, chrono::Utc::now().date_naive())?;
+    Ok(positions
+        .into_iter()
+        .map(|pos| {
+            tasks[pos].1.priority = priority;
+            (pos + 1, tasks[pos].1.clone())
+        })
+        .collect())
+}
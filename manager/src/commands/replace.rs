@@ -1,57 +1,38 @@
-use crate::TodoManager;
+use crate::{TaskRef, TodoManager};
 use todo_txt_model::prelude::*;
 
 impl TodoManager {
-    #[tracing::instrument(parent = None, skip(self))]
-    pub fn replace(&self, task_id: usize, mut new_task: Task) -> Result<(usize, Task, Task)> {
-        let tasks = self.list(
-            super::list::Filter::default(),
-            super::list::Order::default(),
-        )?;
+    #[tracing::instrument(parent = None, skip(self, task_ref))]
+    pub fn replace(
+        &self,
+        task_ref: impl Into<TaskRef>,
+        mut new_task: Task,
+    ) -> Result<(usize, Task, Task)> {
+        let task_ref = task_ref.into();
+        tracing::debug!("task: {}", task_ref);
+        let _lock = self.lock()?;
+        let (tasks, layout) = self.read_for_write()?;
 
-        if new_task.created_date.is_none() {
-            new_task.created_date = Some(chrono::Utc::now().date_naive());
-        }
-        if new_task.state == TaskState::Done && new_task.completed_date.is_none() {
-            new_task.completed_date = Some(chrono::Utc::now().date_naive());
-        }
+        crate::commands::add::fill_dates(&mut new_task, self.date_on_add);
 
-        let ((task_id, old), tasks) = self.take_task_by_id(task_id, tasks)?;
+        let ((task_id, old), tasks) = self.take_task(&task_ref, tasks)?;
+        if new_task.uid().is_none() {
+            new_task.set_uid(old.uid().map(str::to_string));
+        }
 
         let tasks = self.set_task_at(task_id, new_task.clone(), tasks)?;
-        let (todos, dones) = self.split_tasks_todo_and_done(tasks)?;
-        crate::commands::write_tasks(&self.todo_file, &todos)?;
-        crate::commands::write_tasks(&self.done_file, &dones)?;
+        self.save_tasks(&format!("replace {}", task_ref), tasks, &layout)?;
         Ok((task_id, old, new_task))
     }
 
-    #[cfg(any(feature = "rt_tokio", feature = "rt_async_std", feature = "rt_smol"))]
-    #[tracing::instrument(parent = None, skip(self))]
+    #[cfg(feature = "async")]
     pub async fn replace_async(
         &self,
-        task_id: usize,
-        mut new_task: Task,
+        task_ref: impl Into<TaskRef>,
+        new_task: Task,
     ) -> Result<(usize, Task, Task)> {
-        let tasks = self
-            .list_async(
-                super::list::Filter::default(),
-                super::list::Order::default(),
-            )
-            .await?;
-
-        if new_task.created_date.is_none() {
-            new_task.created_date = Some(chrono::Utc::now().date_naive());
-        }
-        if new_task.state == TaskState::Done && new_task.completed_date.is_none() {
-            new_task.completed_date = Some(chrono::Utc::now().date_naive());
-        }
-
-        let ((task_id, old), tasks) = self.take_task_by_id(task_id, tasks)?;
-
-        let tasks = self.set_task_at(task_id, new_task.clone(), tasks)?;
-        let (todos, dones) = self.split_tasks_todo_and_done(tasks)?;
-        crate::commands::write_tasks_async(&self.todo_file, &todos).await?;
-        crate::commands::write_tasks_async(&self.done_file, &dones).await?;
-        Ok((task_id, old, new_task))
+        let task_ref = task_ref.into();
+        self.unblock(move |manager| manager.replace(task_ref, new_task))
+            .await
     }
 }
@@ -0,0 +1,162 @@
+use crate::TodoManager;
+use chrono::SubsecRound;
+use todo_txt_model::prelude::*;
+
+/// Format of the time of a report line, like `date +%Y-%m-%dT%T` in todo.sh.
+const TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+/// A line of report.txt: when the report was made and how many tasks were open and done,
+/// e.g. `2021-01-10T09:30:00 12 40`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReportEntry {
+    /// Local time of the report
+    pub time: chrono::NaiveDateTime,
+    pub open: usize,
+    pub done: usize,
+}
+
+impl std::fmt::Display for ReportEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {}",
+            self.time.format(TIME_FORMAT),
+            self.open,
+            self.done
+        )
+    }
+}
+
+impl std::str::FromStr for ReportEntry {
+    type Err = TodoTxtRsError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || TodoTxtRsError::InvalidReport(s.to_string());
+        let mut fields = s.split_whitespace();
+        let time = fields
+            .next()
+            .and_then(|time| chrono::NaiveDateTime::parse_from_str(time, TIME_FORMAT).ok())
+            .ok_or_else(invalid)?;
+        let mut count = || {
+            fields
+                .next()
+                .and_then(|n| n.parse().ok())
+                .ok_or_else(invalid)
+        };
+        let (open, done) = (count()?, count()?);
+        if fields.next().is_some() {
+            return Err(invalid());
+        }
+        Ok(Self { time, open, done })
+    }
+}
+
+impl TodoManager {
+    /// Count the open and done tasks of todo.txt and done.txt and append them to the report
+    /// file, like `todo.sh report`.
+    /// note: unlike todo.sh nothing is archived first, done tasks in todo.txt count as done
+    /// note: the report is not recorded in the history
+    #[tracing::instrument(parent = None, skip(self))]
+    pub fn report(&self) -> Result<ReportEntry> {
+        let _lock = self.lock()?;
+        let tasks = self.read_all_tasks()?;
+        let (done, open): (Vec<_>, Vec<_>) = tasks
+            .iter()
+            .filter(|(_, t)| !crate::commands::is_placeholder(t))
+            .partition(|(_, t)| t.is_done());
+        let entry = ReportEntry {
+            time: chrono::Local::now().naive_local().trunc_subsecs(0),
+            open: open.len(),
+            done: done.len(),
+        };
+        let mut lines = self.storage.load(&self.report_file)?;
+        lines.push(entry.to_string());
+        self.storage.save(&[(self.report_file.clone(), lines)])?;
+        Ok(entry)
+    }
+
+    #[cfg(feature = "async")]
+    pub async fn report_async(&self) -> Result<ReportEntry> {
+        self.unblock(|manager| manager.report()).await
+    }
+
+    /// Entries of the report file, oldest first.
+    /// note: blank lines and lines that are not an entry are skipped, the latter with a warning
+    #[tracing::instrument(parent = None, skip(self))]
+    pub fn read_report(&self) -> Result<Vec<ReportEntry>> {
+        Ok(parse_report(&self.storage.load(&self.report_file)?))
+    }
+
+    #[cfg(feature = "async")]
+    pub async fn read_report_async(&self) -> Result<Vec<ReportEntry>> {
+        self.unblock(|manager| manager.read_report()).await
+    }
+}
+
+fn parse_report(lines: &[String]) -> Vec<ReportEntry> {
+    lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .filter_map(|(idx, line)| match line.parse() {
+            Ok(entry) => Some(entry),
+            Err(e) => {
+                tracing::warn!("skipped report line {}: {}", idx + 1, e);
+                None
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{MemoryStorage, Storage};
+
+    #[test]
+    fn test_parse_report() {
+        let lines = ["2009-03-14T12:33:57 4 1", "", "2009-03-15T08:00:00 2 5"].map(String::from);
+        let entries = parse_report(&lines);
+        assert_eq!(entries.len(), 2);
+        assert_eq!((entries[1].open, entries[1].done), (2, 5));
+        assert_eq!(entries[0].to_string(), lines[0]);
+        for line in [
+            "2009-03-14 4 1",
+            "2009-03-14T12:33:57 4",
+            "2009-03-14T12:33:57 4 1 0",
+        ] {
+            assert!(matches!(
+                line.parse::<ReportEntry>(),
+                Err(TodoTxtRsError::InvalidReport(_))
+            ));
+            assert_eq!(parse_report(&[line.to_string(), lines[0].clone()]).len(), 1);
+        }
+    }
+
+    #[test]
+    fn test_report() {
+        let storage = MemoryStorage::new();
+        let manager = crate::TodoManagerBuilder::new()
+            .with_env(false)
+            .build()
+            .unwrap()
+            .with_storage(storage.clone())
+            .with_archive_policy(crate::ArchivePolicy::Manual);
+        storage
+            .save(&[
+                ("todo.txt".to_string(), vec!["a".into(), "x b".into()]),
+                ("done.txt".to_string(), vec!["x c".into()]),
+                (
+                    "report.txt".to_string(),
+                    vec!["2009-03-14T12:33:57 4 1".into(), "not a report".into()],
+                ),
+            ])
+            .unwrap();
+
+        let entry = manager.report().unwrap();
+        assert_eq!((entry.open, entry.done), (1, 2));
+        assert_eq!(manager.read_report().unwrap().last().copied(), Some(entry));
+        assert_eq!(manager.read_report().unwrap().len(), 2);
+        assert_eq!(storage.load("report.txt").unwrap().len(), 3);
+    }
+}
@@ -0,0 +1,286 @@
+use crate::TodoManager;
+use todo_txt_model::prelude::*;
+
+/// Largest number of weeks in `Stats::weeks`, about twenty years.
+pub const MAX_STATS_WEEKS: usize = 1040;
+
+#[derive(Debug, Clone)]
+pub struct StatsOptions {
+    /// Number of weeks in `Stats::weeks`, the current one included.
+    /// note: clamped to `MAX_STATS_WEEKS`
+    pub weeks: usize,
+    /// Number of tasks in `Stats::oldest_open`.
+    pub oldest: usize,
+}
+
+impl Default for StatsOptions {
+    fn default() -> Self {
+        Self {
+            weeks: 8,
+            oldest: 5,
+        }
+    }
+}
+
+/// Counts and dates computed over todo.txt and done.txt.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+pub struct Stats {
+    pub open: usize,
+    pub done: usize,
+    /// Every project by name. A task with several projects counts for each of them, and
+    /// once for a project it lists twice.
+    pub projects: Vec<GroupStats>,
+    /// Every context by name.
+    pub contexts: Vec<GroupStats>,
+    /// Average number of days from the creation to the completion of done tasks.
+    /// note: done tasks without both dates are left out, `None` when there are none
+    pub average_lead_time: Option<f64>,
+    /// Tasks created and completed per week and their completion rate, oldest first.
+    pub weeks: Vec<WeekStats>,
+    /// Open tasks with the oldest creation date, oldest first.
+    pub oldest_open: Vec<OpenTask>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct GroupStats {
+    pub name: String,
+    pub open: usize,
+    pub done: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct WeekStats {
+    /// Monday of the week
+    pub start: chrono::NaiveDate,
+    pub created: usize,
+    pub completed: usize,
+    /// Share of the tasks created in the week that are done, whenever they were completed.
+    /// note: between 0 and 1, `None` when no task was created in the week
+    pub completion_rate: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct OpenTask {
+    pub id: usize,
+    pub created_date: chrono::NaiveDate,
+    /// Days since the creation date
+    pub age: i64,
+    /// The task as written in todo.txt
+    pub task: String,
+}
+
+impl TodoManager {
+    /// Compute the statistics of every task in todo.txt and done.txt.
+    #[tracing::instrument(parent = None, skip(self))]
+    pub fn stats(&self, options: StatsOptions) -> Result<Stats> {
+        if self.storage.needs_recovery() {
+            // finish the interrupted write under the lock
+            drop(self.lock()?);
+        }
+        let tasks = self.read_all_tasks()?;
+        Ok(compute_stats(
+            &tasks,
+            &options,
+            chrono::Utc::now().date_naive(),
+        ))
+    }
+
+    #[cfg(feature = "async")]
+    pub async fn stats_async(&self, options: StatsOptions) -> Result<Stats> {
+        self.unblock(move |manager| manager.stats(options)).await
+    }
+}
+
+fn compute_stats(
+    tasks: &[(usize, Task)],
+    options: &StatsOptions,
+    today: chrono::NaiveDate,
+) -> Stats {
+    let tasks = tasks
+        .iter()
+        .filter(|(_, t)| !crate::commands::is_placeholder(t))
+        .collect::<Vec<_>>();
+    let mut stats = Stats::default();
+    let mut projects = std::collections::BTreeMap::<&str, GroupStats>::new();
+    let mut contexts = std::collections::BTreeMap::<&str, GroupStats>::new();
+    let mut lead_times = Vec::new();
+    for (_, task) in &tasks {
+        let done = task.is_done();
+        if done {
+            stats.done += 1;
+        } else {
+            stats.open += 1;
+        }
+        for (groups, names) in [
+            (&mut projects, &task.description.project),
+            (&mut contexts, &task.description.context),
+        ] {
+            for name in names.iter().collect::<std::collections::BTreeSet<_>>() {
+                let group = groups.entry(name).or_insert_with(|| GroupStats {
+                    name: name.clone(),
+                    open: 0,
+                    done: 0,
+                });
+                if done {
+                    group.done += 1;
+                } else {
+                    group.open += 1;
+                }
+            }
+        }
+        if let (true, Some(created), Some(completed)) =
+            (done, task.created_date, task.completed_date)
+        {
+            lead_times.push((completed - created).num_days());
+        }
+    }
+    stats.projects = projects.into_values().collect();
+    stats.contexts = contexts.into_values().collect();
+    if !lead_times.is_empty() {
+        stats.average_lead_time =
+            Some(lead_times.iter().sum::<i64>() as f64 / lead_times.len() as f64);
+    }
+
+    let this_week = week_start(today);
+    let weeks = options.weeks.min(MAX_STATS_WEEKS);
+    stats.weeks = (0..weeks)
+        .rev()
+        .filter_map(|ago| {
+            Some(WeekStats {
+                start: this_week.checked_sub_signed(chrono::Duration::weeks(ago as i64))?,
+                created: 0,
+                completed: 0,
+                completion_rate: None,
+            })
+        })
+        .collect();
+    let weeks = stats.weeks.len();
+    let week_of = |date: chrono::NaiveDate| {
+        let weeks_ago = (this_week - week_start(date)).num_weeks();
+        usize::try_from(weeks_ago)
+            .ok()
+            .filter(|ago| *ago < weeks)
+            .map(|ago| weeks - 1 - ago)
+    };
+    let mut created_done = vec![0; weeks];
+    for (_, task) in &tasks {
+        if let Some(week) = task.created_date.and_then(week_of) {
+            stats.weeks[week].created += 1;
+            if task.is_done() {
+                created_done[week] += 1;
+            }
+        }
+        if let Some(week) = task.completed_date.and_then(week_of) {
+            stats.weeks[week].completed += 1;
+        }
+    }
+    for (week, done) in stats.weeks.iter_mut().zip(created_done) {
+        if week.created > 0 {
+            week.completion_rate = Some(done as f64 / week.created as f64);
+        }
+    }
+
+    let mut open = tasks
+        .iter()
+        .filter(|(_, t)| !t.is_done())
+        .filter_map(|(id, t)| {
+            t.created_date.map(|created_date| OpenTask {
+                id: *id,
+                created_date,
+                age: (today - created_date).num_days(),
+                task: todo_txt_serializer::to_string(t),
+            })
+        })
+        .collect::<Vec<_>>();
+    open.sort_by_key(|t| (t.created_date, t.id));
+    open.truncate(options.oldest);
+    stats.oldest_open = open;
+    stats
+}
+
+/// Monday of the week of `date`.
+fn week_start(date: chrono::NaiveDate) -> chrono::NaiveDate {
+    use chrono::Datelike;
+    date - chrono::Duration::days(i64::from(date.weekday().num_days_from_monday()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_compute_stats() {
+        let tasks = [
+            "2021-01-01 a +work @office",
+            "x 2021-01-12 2021-01-02 b +work",
+            "x 2021-01-13 2021-01-11 c +home @office +home",
+            "x 2020-12-01 no creation date +work",
+            "",
+            "2021-01-12 d",
+            "x 2021-01-13 2021-01-11 e",
+        ]
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            let task = if line.is_empty() {
+                crate::commands::placeholder()
+            } else {
+                todo_txt_serializer::from_str(line).unwrap()
+            };
+            (i + 1, task)
+        })
+        .collect::<Vec<_>>();
+        let today = chrono::NaiveDate::from_ymd_opt(2021, 1, 14).unwrap();
+        let options = StatsOptions {
+            weeks: 2,
+            oldest: 1,
+        };
+        let stats = compute_stats(&tasks, &options, today);
+
+        assert_eq!((stats.open, stats.done), (2, 4));
+        assert_eq!(
+            stats.projects,
+            vec![
+                GroupStats {
+                    name: "home".to_string(),
+                    open: 0,
+                    done: 1
+                },
+                GroupStats {
+                    name: "work".to_string(),
+                    open: 1,
+                    done: 2
+                },
+            ]
+        );
+        assert_eq!(stats.contexts.len(), 1);
+        assert_eq!(stats.average_lead_time, Some(14.0 / 3.0));
+        assert_eq!(
+            stats
+                .weeks
+                .iter()
+                .map(|w| (
+                    w.start.to_string(),
+                    w.created,
+                    w.completed,
+                    w.completion_rate
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                ("2021-01-04".to_string(), 0, 0, None),
+                ("2021-01-11".to_string(), 3, 3, Some(2.0 / 3.0)),
+            ]
+        );
+        assert_eq!(stats.oldest_open.len(), 1);
+        assert_eq!(stats.oldest_open[0].id, 1);
+        assert_eq!(stats.oldest_open[0].age, 13);
+
+        let options = StatsOptions {
+            weeks: usize::MAX,
+            oldest: 0,
+        };
+        let stats = compute_stats(&tasks, &options, today);
+        assert_eq!(stats.weeks.len(), MAX_STATS_WEEKS);
+        assert_eq!(stats.weeks.last().unwrap().created, 3);
+    }
+}
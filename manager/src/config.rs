@@ -0,0 +1,333 @@
+use todo_txt_model::prelude::*;
+
+/// Settings of a `TodoManager`, read from configuration files, the environment or given by
+/// the command line. `None` leaves the setting to a lower layer.
+///
+/// `TodoManagerBuilder` merges the layers in this order, later ones overriding earlier ones:
+/// 1. the defaults
+/// 2. the configuration file given by `with_config_file`, otherwise the todo.sh `todo.cfg`
+///    (`TODOTXT_CFG_FILE`, `~/.todo/config`, `~/todo.cfg`, `~/.todo.cfg` or
+///    `$XDG_CONFIG_HOME/todo/config`) followed by the TOML file (`TODOTXT_TOML_FILE` or
+///    `$XDG_CONFIG_HOME/todo-txt-rs/config.toml`); when both exist, the TOML file wins
+///    over todo.cfg for the settings it has
+/// 3. `.env` and the environment variables, named like in todo.cfg
+/// 4. the settings given by `with_config` and the other builder methods, e.g. CLI flags
+///
+/// note: display settings of todo.cfg like `PRI_*` and `COLOR_*` are ignored, the output
+/// is not colored
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// `TODO_DIR`
+    pub todo_dir: Option<std::path::PathBuf>,
+    /// `TODO_FILE`, relative to the data directory unless absolute
+    pub todo_file: Option<std::path::PathBuf>,
+    /// `DONE_FILE`
+    pub done_file: Option<std::path::PathBuf>,
+    /// `REPORT_FILE`
+    pub report_file: Option<std::path::PathBuf>,
+    /// `TODOTXT_LIST`: name of the active list, e.g. `work` for work.txt and work.done.txt
+    /// note: a list other than `todo` takes the place of the todo, done and report files
+    pub list: Option<String>,
+    /// `TODOTXT_DATE_ON_ADD`: set the creation date of added tasks
+    pub date_on_add: Option<bool>,
+    /// `TODOTXT_PRESERVE_LINE_NUMBERS`
+    pub preserve_line_numbers: Option<bool>,
+    /// `TODOTXT_AUTO_ARCHIVE`: move done tasks to done.txt on every change
+    pub auto_archive: Option<bool>,
+    /// `TODOTXT_AUTO_ID`
+    pub auto_id: Option<bool>,
+    /// `TODOTXT_HISTORY_DEPTH`
+    pub history_depth: Option<usize>,
+}
+
+impl Config {
+    /// Settings of `other` where it has them, of `self` otherwise.
+    pub fn merge(mut self, other: Config) -> Self {
+        self.todo_dir = other.todo_dir.or(self.todo_dir);
+        self.todo_file = other.todo_file.or(self.todo_file);
+        self.done_file = other.done_file.or(self.done_file);
+        self.report_file = other.report_file.or(self.report_file);
+        self.list = other.list.or(self.list);
+        self.date_on_add = other.date_on_add.or(self.date_on_add);
+        self.preserve_line_numbers = other.preserve_line_numbers.or(self.preserve_line_numbers);
+        self.auto_archive = other.auto_archive.or(self.auto_archive);
+        self.auto_id = other.auto_id.or(self.auto_id);
+        self.history_depth = other.history_depth.or(self.history_depth);
+        self
+    }
+
+    /// Read a TOML file when its extension is `.toml`, a todo.cfg otherwise.
+    pub fn from_file(path: &std::path::Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        if path.extension().is_some_and(|ext| ext == "toml") {
+            Self::from_toml(&content).map_err(|e| in_file(path, e))
+        } else {
+            Self::from_todo_cfg(&content).map_err(|e| in_file(path, e))
+        }
+    }
+
+    /// Settings of a TOML file, with the keys named like the fields.
+    pub fn from_toml(content: &str) -> Result<Self> {
+        toml::from_str(content).map_err(|e| TodoTxtRsError::InvalidConfig(e.message().to_string()))
+    }
+
+    /// Settings of a todo.sh configuration file.
+    ///
+    /// Only assignments are read, with or without `export`. Values can be quoted and refer
+    /// to variables assigned before or to the environment, e.g. `"$HOME/todo"`.
+    /// note: assignments using command substitution or an unset variable are skipped, like
+    /// the default `TODO_DIR=$(dirname "$0")`, so the setting falls back to a lower layer
+    pub fn from_todo_cfg(content: &str) -> Result<Self> {
+        let mut vars = Vec::<(String, String)>::new();
+        for line in content.lines() {
+            let Some((name, value)) = parse_assignment(line) else {
+                continue;
+            };
+            let lookup = |var: &str| {
+                vars.iter()
+                    .rev()
+                    .find(|(name, _)| name == var)
+                    .map(|(_, value)| value.clone())
+                    .or_else(|| std::env::var(var).ok())
+            };
+            match expand(value, lookup) {
+                Some(value) => vars.push((name.to_string(), value)),
+                None => tracing::warn!("skipped todo.cfg line: {}", line),
+            }
+        }
+        Self::from_vars(vars)
+    }
+
+    /// Settings of the environment variables.
+    pub fn from_env() -> Result<Self> {
+        Self::from_vars(std::env::vars())
+    }
+
+    /// Settings of the default configuration files, see `Config`.
+    pub fn from_default_files() -> Result<Self> {
+        let cfg_file = match std::env::var_os("TODOTXT_CFG_FILE") {
+            Some(file) => Some(std::path::PathBuf::from(file)),
+            None => home_dir().and_then(|home| {
+                [
+                    home.join(".todo").join("config"),
+                    home.join("todo.cfg"),
+                    home.join(".todo.cfg"),
+                ]
+                .into_iter()
+                .chain(config_dir().map(|dir| dir.join("todo").join("config")))
+                .find(|file| file.is_file())
+            }),
+        };
+        let toml_file = match std::env::var_os("TODOTXT_TOML_FILE") {
+            Some(file) => Some(std::path::PathBuf::from(file)),
+            None => config_dir()
+                .map(|dir| dir.join("todo-txt-rs").join("config.toml"))
+                .filter(|file| file.is_file()),
+        };
+        Self::from_files(cfg_file.as_deref(), toml_file.as_deref())
+    }
+
+    /// Settings of a todo.cfg overridden by those of a TOML file.
+    fn from_files(
+        cfg_file: Option<&std::path::Path>,
+        toml_file: Option<&std::path::Path>,
+    ) -> Result<Self> {
+        let mut config = Self::default();
+        for file in cfg_file.into_iter().chain(toml_file) {
+            config = config.merge(Self::from_file(file)?);
+        }
+        Ok(config)
+    }
+
+    /// Settings of variables named like in todo.cfg, ignoring the others.
+    /// note: empty values are ignored
+    fn from_vars(vars: impl IntoIterator<Item = (String, String)>) -> Result<Self> {
+        let mut config = Self::default();
+        for (name, value) in vars {
+            if value.is_empty() {
+                continue;
+            }
+            match name.as_str() {
+                "TODO_DIR" => config.todo_dir = Some(value.into()),
+                "TODO_FILE" => config.todo_file = Some(value.into()),
+                "DONE_FILE" => config.done_file = Some(value.into()),
+                "REPORT_FILE" => config.report_file = Some(value.into()),
+                "TODOTXT_LIST" => config.list = Some(value),
+                "TODOTXT_DATE_ON_ADD" => config.date_on_add = Some(parse_bool(&name, &value)?),
+                "TODOTXT_PRESERVE_LINE_NUMBERS" => {
+                    config.preserve_line_numbers = Some(parse_bool(&name, &value)?)
+                }
+                "TODOTXT_AUTO_ARCHIVE" => config.auto_archive = Some(parse_bool(&name, &value)?),
+                "TODOTXT_AUTO_ID" => config.auto_id = Some(parse_bool(&name, &value)?),
+                "TODOTXT_HISTORY_DEPTH" => {
+                    config.history_depth = Some(value.parse().map_err(|_| {
+                        TodoTxtRsError::InvalidConfig(format!("{}={}", name, value))
+                    })?)
+                }
+                _ => {}
+            }
+        }
+        Ok(config)
+    }
+}
+
+fn parse_bool(name: &str, value: &str) -> Result<bool> {
+    match value.to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Ok(true),
+        "0" | "false" | "no" | "off" => Ok(false),
+        _ => Err(TodoTxtRsError::InvalidConfig(format!("{}={}", name, value))),
+    }
+}
+
+fn in_file(path: &std::path::Path, error: TodoTxtRsError) -> TodoTxtRsError {
+    match error {
+        TodoTxtRsError::InvalidConfig(message) => {
+            TodoTxtRsError::InvalidConfig(format!("{}: {}", path.display(), message))
+        }
+        e => e,
+    }
+}
+
+fn home_dir() -> Option<std::path::PathBuf> {
+    std::env::var_os("HOME").map(std::path::PathBuf::from)
+}
+
+fn config_dir() -> Option<std::path::PathBuf> {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .map(std::path::PathBuf::from)
+        .or_else(|| home_dir().map(|home| home.join(".config")))
+}
+
+/// Name and raw value of `NAME=value` or `export NAME=value`.
+fn parse_assignment(line: &str) -> Option<(&str, &str)> {
+    let line = line.trim();
+    let line = line.strip_prefix("export ").unwrap_or(line).trim_start();
+    let (name, value) = line.split_once('=')?;
+    let mut chars = name.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    valid.then_some((name, value))
+}
+
+/// Value of a shell word: quotes removed, variables expanded and a trailing comment dropped.
+/// note: `None` when it uses command substitution or an unset variable
+fn expand(value: &str, lookup: impl Fn(&str) -> Option<String>) -> Option<String> {
+    let mut result = String::new();
+    let mut chars = value.chars().peekable();
+    let mut quote = None;
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some('\''), '\'') | (Some('"'), '"') => quote = None,
+            (Some('\''), c) => result.push(c),
+            (None, '\'' | '"') => quote = Some(c),
+            (None, ' ' | '\t' | ';') => break,
+            (_, '`') => return None,
+            (_, '\\') => result.push(chars.next()?),
+            (_, '$') => {
+                let name = match chars.peek() {
+                    Some('(') => return None,
+                    Some('{') => {
+                        chars.next();
+                        let name: String = chars.by_ref().take_while(|c| *c != '}').collect();
+                        name
+                    }
+                    _ => {
+                        let mut name = String::new();
+                        while let Some(c) =
+                            chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '_')
+                        {
+                            name.push(c);
+                        }
+                        name
+                    }
+                };
+                result.push_str(&lookup(&name)?);
+            }
+            (_, c) => result.push(c),
+        }
+    }
+    Some(result)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_from_todo_cfg() {
+        let config = Config::from_todo_cfg(
+            r#"
+# === EDIT FILE LOCATIONS BELOW ===
+export TODO_DIR=$(dirname "$0")
+export TODO_FILE="$TODO_TEST_UNSET/todo.txt"
+TODO_DIR='/srv/todo'
+export DONE_FILE="$TODO_DIR/done.txt"  # archive
+export REPORT_FILE=${TODO_DIR}/report\ file.txt
+export TODOTXT_DATE_ON_ADD=0
+export TODOTXT_PRESERVE_LINE_NUMBERS=1
+export YELLOW='\\033[0;33m'
+export PRI_A=$YELLOW
+export COLOR_DONE=$UNSET_COLOR
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config,
+            Config {
+                todo_dir: Some("/srv/todo".into()),
+                done_file: Some("/srv/todo/done.txt".into()),
+                report_file: Some("/srv/todo/report file.txt".into()),
+                date_on_add: Some(false),
+                preserve_line_numbers: Some(true),
+                ..Default::default()
+            }
+        );
+        assert!(matches!(
+            Config::from_todo_cfg("export TODOTXT_AUTO_ARCHIVE=maybe"),
+            Err(TodoTxtRsError::InvalidConfig(_))
+        ));
+    }
+
+    #[test]
+    fn test_from_toml_and_merge() {
+        let file = Config::from_toml(
+            "todo_dir = \"/srv/todo\"\nauto_archive = false\nhistory_depth = 5\nlist = \"work\"\n",
+        )
+        .unwrap();
+        let flags = Config {
+            auto_archive: Some(true),
+            ..Default::default()
+        };
+        let config = file.merge(flags);
+        assert_eq!(config.todo_dir, Some("/srv/todo".into()));
+        assert_eq!(config.auto_archive, Some(true));
+        assert_eq!(config.history_depth, Some(5));
+        assert_eq!(config.list.as_deref(), Some("work"));
+        assert!(matches!(
+            Config::from_toml("date_on_ad = true"),
+            Err(TodoTxtRsError::InvalidConfig(_))
+        ));
+    }
+
+    #[test]
+    fn test_toml_file_wins_over_todo_cfg() {
+        let dir = std::env::temp_dir().join(format!("todo-txt-rs-config-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let cfg_file = dir.join("todo.cfg");
+        let toml_file = dir.join("config.toml");
+        std::fs::write(
+            &cfg_file,
+            "export TODO_DIR=/srv/todo\nexport TODOTXT_AUTO_ARCHIVE=1\nexport TODOTXT_HISTORY_DEPTH=3\n",
+        )
+        .unwrap();
+        std::fs::write(&toml_file, "auto_archive = false\nhistory_depth = 5\n").unwrap();
+        let config = Config::from_files(Some(&cfg_file), Some(&toml_file)).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(config.todo_dir, Some("/srv/todo".into()));
+        assert_eq!(config.auto_archive, Some(false));
+        assert_eq!(config.history_depth, Some(5));
+    }
+}
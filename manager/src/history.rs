@@ -0,0 +1,298 @@
+use crate::TodoManager;
+use todo_txt_model::prelude::*;
+
+/// Number of operations kept for `undo` unless configured otherwise.
+pub const DEFAULT_HISTORY_DEPTH: usize = 20;
+
+/// Contents of the data files touched by one operation, before and after it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct HistoryEntry {
+    pub(crate) operation: String,
+    pub(crate) files: Vec<FileChange>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct FileChange {
+    /// File name in the storage
+    pub(crate) name: String,
+    pub(crate) before: Vec<String>,
+    pub(crate) after: Vec<String>,
+}
+
+/// Operations that can be undone, oldest first, and operations that were undone, in undo order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct History {
+    pub(crate) undo: Vec<HistoryEntry>,
+    pub(crate) redo: Vec<HistoryEntry>,
+}
+
+/// Lines of every data file, taken before an operation.
+pub(crate) type Snapshot = Vec<(String, Vec<String>)>;
+
+const UNDO_HEADER: &str = "undo";
+const REDO_HEADER: &str = "redo";
+const FILE_HEADER: &str = "file";
+const BEFORE_MARKER: char = '<';
+const AFTER_MARKER: char = '>';
+
+impl History {
+    /// Record a new operation. Anything that was undone can not be redone anymore.
+    pub(crate) fn push(&mut self, entry: HistoryEntry, depth: usize) {
+        self.undo.push(entry);
+        self.redo.clear();
+        if self.undo.len() > depth {
+            self.undo.drain(..self.undo.len() - depth);
+        }
+    }
+
+    /// One `undo\t<operation>` or `redo\t<operation>` line per entry, followed by a
+    /// `file\t<name>` line per touched file and its lines marked with `<` (before) or `>` (after).
+    pub(crate) fn to_lines(&self) -> Vec<String> {
+        let mut out = Vec::new();
+        let entries = self
+            .undo
+            .iter()
+            .map(|e| (UNDO_HEADER, e))
+            .chain(self.redo.iter().map(|e| (REDO_HEADER, e)));
+        for (header, entry) in entries {
+            out.push(format!("{}\t{}", header, entry.operation));
+            for file in &entry.files {
+                out.push(format!("{}\t{}", FILE_HEADER, file.name));
+                for line in &file.before {
+                    out.push(format!("{}{}", BEFORE_MARKER, line));
+                }
+                for line in &file.after {
+                    out.push(format!("{}{}", AFTER_MARKER, line));
+                }
+            }
+        }
+        out
+    }
+
+    /// note: marked lines are file lines even when they hold a tab, only the other lines are
+    /// headers
+    pub(crate) fn from_lines(lines: &[String]) -> Self {
+        let mut history = Self::default();
+        for line in lines {
+            let marked = line
+                .strip_prefix(BEFORE_MARKER)
+                .map(|l| (true, l))
+                .or_else(|| line.strip_prefix(AFTER_MARKER).map(|l| (false, l)));
+            if let Some((before, content)) = marked {
+                let Some(file) = history.last_entry().and_then(|e| e.files.last_mut()) else {
+                    tracing::warn!("history line outside of an entry: {:?}", line);
+                    continue;
+                };
+                if before {
+                    file.before.push(content.to_string());
+                } else {
+                    file.after.push(content.to_string());
+                }
+                continue;
+            }
+            let Some((header, value)) = line.split_once('\t') else {
+                tracing::warn!("unknown history line: {:?}", line);
+                continue;
+            };
+            let entry = HistoryEntry {
+                operation: value.to_string(),
+                files: Vec::new(),
+            };
+            match header {
+                UNDO_HEADER => history.undo.push(entry),
+                REDO_HEADER => history.redo.push(entry),
+                FILE_HEADER => {
+                    if let Some(entry) = history.last_entry() {
+                        entry.files.push(FileChange {
+                            name: value.to_string(),
+                            before: Vec::new(),
+                            after: Vec::new(),
+                        });
+                    }
+                }
+                _ => tracing::warn!("unknown history line: {:?}", line),
+            }
+        }
+        history
+    }
+
+    fn last_entry(&mut self) -> Option<&mut HistoryEntry> {
+        if self.redo.is_empty() {
+            self.undo.last_mut()
+        } else {
+            self.redo.last_mut()
+        }
+    }
+}
+
+impl TodoManager {
+    /// Revert the last operation and return its description.
+    /// note: fails with `HistoryConflict` when the files were changed by something else since
+    #[tracing::instrument(parent = None, skip(self))]
+    pub fn undo(&self) -> Result<String> {
+        let _lock = self.lock()?;
+        let mut history = self.read_history()?;
+        let entry = history.undo.pop().ok_or(TodoTxtRsError::NothingToUndo)?;
+        self.check_history(&entry, |f| &f.after)?;
+        let files = self.history_files(&entry, |f| &f.before);
+        self.storage.save(&files)?;
+        let operation = entry.operation.clone();
+        history.redo.push(entry);
+        self.write_history(&history)?;
+        Ok(operation)
+    }
+
+    /// Apply the last undone operation again and return its description.
+    #[tracing::instrument(parent = None, skip(self))]
+    pub fn redo(&self) -> Result<String> {
+        let _lock = self.lock()?;
+        let mut history = self.read_history()?;
+        let entry = history.redo.pop().ok_or(TodoTxtRsError::NothingToRedo)?;
+        self.check_history(&entry, |f| &f.before)?;
+        let files = self.history_files(&entry, |f| &f.after);
+        self.storage.save(&files)?;
+        let operation = entry.operation.clone();
+        history.undo.push(entry);
+        self.write_history(&history)?;
+        Ok(operation)
+    }
+
+    #[cfg(feature = "async")]
+    pub async fn undo_async(&self) -> Result<String> {
+        self.unblock(|manager| manager.undo()).await
+    }
+
+    #[cfg(feature = "async")]
+    pub async fn redo_async(&self) -> Result<String> {
+        self.unblock(|manager| manager.redo()).await
+    }
+
+    /// Lines of the data files, to be passed to `record` after the operation.
+    /// note: returns an empty snapshot when the history is disabled
+    pub(crate) fn snapshot(&self) -> Result<Snapshot> {
+        if self.history_depth == 0 {
+            return Ok(Vec::new());
+        }
+        [&self.todo_file, &self.done_file]
+            .into_iter()
+            .map(|name| Ok((name.clone(), self.storage.load(name)?)))
+            .collect()
+    }
+
+    /// Add the operation that turned `before` into the current files to the history.
+    pub(crate) fn record(&self, operation: &str, before: Snapshot) -> Result<()> {
+        if self.history_depth == 0 {
+            return Ok(());
+        }
+        let mut files = Vec::new();
+        for (name, before) in before {
+            let after = self.storage.load(&name)?;
+            if before != after {
+                files.push(FileChange {
+                    name,
+                    before,
+                    after,
+                });
+            }
+        }
+        if files.is_empty() {
+            return Ok(());
+        }
+        let mut history = self.read_history()?;
+        history.push(
+            HistoryEntry {
+                operation: operation.to_string(),
+                files,
+            },
+            self.history_depth,
+        );
+        self.write_history(&history)
+    }
+
+    fn check_history(
+        &self,
+        entry: &HistoryEntry,
+        expected: impl Fn(&FileChange) -> &Vec<String>,
+    ) -> Result<()> {
+        for file in &entry.files {
+            if &self.storage.load(&file.name)? != expected(file) {
+                return Err(TodoTxtRsError::HistoryConflict(file.name.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    fn history_files(
+        &self,
+        entry: &HistoryEntry,
+        lines: impl Fn(&FileChange) -> &Vec<String>,
+    ) -> Vec<(String, Vec<String>)> {
+        entry
+            .files
+            .iter()
+            .map(|f| (f.name.clone(), lines(f).clone()))
+            .collect()
+    }
+
+    fn read_history(&self) -> Result<History> {
+        Ok(History::from_lines(&self.storage.load(&self.history_file)?))
+    }
+
+    fn write_history(&self, history: &History) -> Result<()> {
+        self.storage
+            .save(&[(self.history_file.clone(), history.to_lines())])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn entry(operation: &str) -> HistoryEntry {
+        HistoryEntry {
+            operation: operation.to_string(),
+            files: vec![FileChange {
+                name: "todo.txt".to_string(),
+                before: vec!["a task".to_string()],
+                after: vec!["a task".to_string(), "<b> task".to_string()],
+            }],
+        }
+    }
+
+    #[test]
+    fn test_history_content_round_trip() {
+        let mut history = History::default();
+        history.push(entry("add a"), 2);
+        history.push(entry("add b"), 2);
+        history.redo.push(entry("delete 1"));
+        assert_eq!(History::from_lines(&history.to_lines()), history);
+
+        history.push(entry("add c"), 2);
+        assert!(history.redo.is_empty());
+        assert_eq!(
+            history
+                .undo
+                .iter()
+                .map(|e| e.operation.as_str())
+                .collect::<Vec<_>>(),
+            vec!["add b", "add c"]
+        );
+    }
+
+    #[test]
+    fn test_history_round_trip_with_tabs() {
+        let mut history = History::default();
+        history.push(
+            HistoryEntry {
+                operation: "add a\ttask".to_string(),
+                files: vec![FileChange {
+                    name: "todo.txt".to_string(),
+                    before: vec!["undo\tnot a header".to_string()],
+                    after: vec!["undo\tnot a header".to_string(), "a\ttask".to_string()],
+                }],
+            },
+            2,
+        );
+        assert_eq!(History::from_lines(&history.to_lines()), history);
+    }
+}
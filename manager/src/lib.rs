@@ -1,45 +1,186 @@
+mod builder;
 mod commands;
+mod config;
+mod history;
+mod lists;
+mod query;
+mod search;
+mod selection;
+mod storage;
+mod task_ref;
+mod transaction;
 
-use std::str::FromStr;
+use commands::FileLayout;
 use todo_txt_model::prelude::*;
 
+pub use builder::TodoManagerBuilder;
+pub use commands::archive::{ArchiveOptions, ArchivePolicy};
 pub use commands::list::*;
+pub use commands::report::ReportEntry;
+pub use commands::stats::{GroupStats, OpenTask, Stats, StatsOptions, WeekStats, MAX_STATS_WEEKS};
+pub use commands::Diagnostic;
+pub use config::Config;
+pub use history::DEFAULT_HISTORY_DEPTH;
+pub use lists::{ListAllWithDiagnostics, DEFAULT_LIST};
+pub use query::{Comparison, DateField, DateValue, Query, RelativeUnit};
+pub use search::{SearchMode, SearchOptions, TextSearch};
+pub use selection::Selection;
+pub use storage::{FileStorage, MemoryStorage, Storage, StorageLock};
+pub use task_ref::{TaskRef, TaskSelector};
+pub use transaction::Transaction;
 
 #[derive(Debug, Clone)]
 pub struct TodoManager {
     pub(crate) todo_dir: std::path::PathBuf,
-    pub(crate) todo_file: std::path::PathBuf,
-    pub(crate) done_file: std::path::PathBuf,
+    pub(crate) storage: std::sync::Arc<dyn Storage>,
+    /// Name of the active list, see `for_list`
+    pub(crate) list: String,
+    /// Names of the files in `storage`
+    pub(crate) todo_file: String,
+    pub(crate) done_file: String,
+    pub(crate) report_file: String,
+    pub(crate) history_file: String,
+    /// Files of the default list, as configured
+    pub(crate) default_list: lists::ListFiles,
+    pub(crate) history_depth: usize,
+    pub(crate) lock_timeout: std::time::Duration,
+    pub(crate) auto_id: bool,
+    pub(crate) date_on_add: bool,
+    pub(crate) preserve_line_numbers: bool,
+    pub(crate) lenient: bool,
+    pub(crate) archive_policy: ArchivePolicy,
 }
 
+/// How long a mutation waits for another process holding the lock.
+pub const DEFAULT_LOCK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
 pub(crate) type GetTaskResult = ((usize, Task), Vec<(usize, Task)>);
+/// Tasks of todo.txt and of done.txt, with their index
+pub(crate) type SplitTasksResult = (Vec<(usize, Task)>, Vec<(usize, Task)>);
+pub(crate) type ReadFilesResult = (Vec<(usize, Task)>, Vec<Diagnostic>, FileLayout);
+pub(crate) type ReadTasksResult = (Vec<Task>, Vec<Diagnostic>, Vec<commands::KeptLine>);
 
 impl TodoManager {
+    /// Manager of the files in `TODO_DIR`, `XDG_DATA_HOME/todo` or `.todo` in the current
+    /// directory, configured by `.env` and the environment.
+    /// note: use `TodoManagerBuilder` for explicit paths
     pub fn new() -> Result<Self> {
-        let _ = dotenvy::dotenv();
-        let todo_dir = if let Ok(dir) = std::env::var("TODO_DIR") {
-            std::path::PathBuf::from(dir)
-        } else if let Ok(home) = std::env::var("XDG_DATA_HOME") {
-            let path = std::path::PathBuf::from_str(&home)?;
-            path.join("todo")
-        } else {
-            let path = std::env::current_dir()?;
-            path.join(".todo")
-        };
-        let todo_file = todo_dir.join("todo.txt");
-        let done_file = todo_dir.join("done.txt");
+        TodoManagerBuilder::new().build()
+    }
+
+    /// Assign an `id:` tag to tasks created by `add` that do not have one.
+    pub fn with_auto_id(mut self, auto_id: bool) -> Self {
+        self.auto_id = auto_id;
+        self
+    }
+
+    /// Set the creation date of added tasks that do not have one.
+    pub fn with_date_on_add(mut self, date_on_add: bool) -> Self {
+        self.date_on_add = date_on_add;
+        self
+    }
+
+    /// Keep the index of every task for as long as it exists: a deleted task leaves a blank
+    /// line and done tasks stay in todo.txt until `archive`, which removes the blank lines.
+    pub fn with_preserve_line_numbers(mut self, preserve_line_numbers: bool) -> Self {
+        self.preserve_line_numbers = preserve_line_numbers;
+        self
+    }
+
+    /// Skip the lines that can not be parsed instead of failing, and keep them and the blank
+    /// lines next to the task that followed them when writing.
+    pub fn with_lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
+    /// How long to wait for the lock held by another process before failing with `LockTimeout`.
+    pub fn with_lock_timeout(mut self, lock_timeout: std::time::Duration) -> Self {
+        self.lock_timeout = lock_timeout;
+        self
+    }
 
-        Ok(Self {
-            todo_dir,
-            todo_file,
-            done_file,
-        })
+    /// Whether done tasks are moved to done.txt on every change or only by `archive`.
+    pub fn with_archive_policy(mut self, archive_policy: ArchivePolicy) -> Self {
+        self.archive_policy = archive_policy;
+        self
+    }
+
+    /// How many operations can be undone. 0 disables the history.
+    pub fn with_history_depth(mut self, history_depth: usize) -> Self {
+        self.history_depth = history_depth;
+        self
+    }
+
+    /// Keep the files in `storage` instead of the data directory, e.g. `MemoryStorage`.
+    pub fn with_storage(mut self, storage: impl Storage + 'static) -> Self {
+        self.storage = std::sync::Arc::new(storage);
+        self
     }
 
     pub fn get_data_dir(&self) -> &std::path::Path {
         &self.todo_dir
     }
 
+    pub fn get_todo_path(&self) -> std::path::PathBuf {
+        self.storage.path(&self.todo_file)
+    }
+
+    pub fn get_done_path(&self) -> std::path::PathBuf {
+        self.storage.path(&self.done_file)
+    }
+
+    pub fn get_report_path(&self) -> std::path::PathBuf {
+        self.storage.path(&self.report_file)
+    }
+
+    /// Where done tasks go, `Manual` when line numbers are preserved.
+    pub(crate) fn effective_archive_policy(&self) -> ArchivePolicy {
+        if self.preserve_line_numbers {
+            ArchivePolicy::Manual
+        } else {
+            self.archive_policy
+        }
+    }
+
+    /// Lock the storage against other processes and finish any interrupted write.
+    /// note: the lock is held until the returned guard is dropped
+    pub(self) fn lock(&self) -> Result<StorageLock> {
+        self.storage.lock(self.lock_timeout)
+    }
+
+    /// Run `f` with a clone of the manager on a blocking thread pool and await its result.
+    /// note: the pool is not tied to an executor, so the async API works with tokio, async-std,
+    /// smol or any other runtime, and file I/O or lock waits never block the executor
+    #[cfg(feature = "async")]
+    pub(crate) async fn unblock<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(TodoManager) -> Result<T> + Send + 'static,
+    ) -> Result<T> {
+        let manager = self.clone();
+        blocking::unblock(move || f(manager)).await
+    }
+
+    /// Get Task by reference
+    /// note: this function will remove the task from the tasks
+    pub(self) fn take_task(
+        &self,
+        task_ref: &TaskRef,
+        tasks: Vec<(usize, Task)>,
+    ) -> Result<GetTaskResult> {
+        match task_ref {
+            TaskRef::Index(task_id) => self.take_task_by_id(*task_id, tasks),
+            TaskRef::Uid(uid) => {
+                let task_id = tasks
+                    .iter()
+                    .position(|(_, t)| t.uid() == Some(uid.as_str()))
+                    .ok_or(TodoTxtRsError::NotFound)?
+                    + 1;
+                self.take_task_by_id(task_id, tasks)
+            }
+        }
+    }
+
     /// Get Task by ID
     /// note: task_id is 1-based index
     /// note: tasks is 0-based index
@@ -49,9 +190,12 @@ impl TodoManager {
         task_id: usize,
         mut tasks: Vec<(usize, Task)>,
     ) -> Result<GetTaskResult> {
-        if task_id > tasks.len() {
+        if task_id == 0 || task_id > tasks.len() {
             return Err(TodoTxtRsError::InvalidIndex);
         }
+        if crate::commands::is_placeholder(&tasks[task_id - 1].1) {
+            return Err(TodoTxtRsError::NotFound);
+        }
         Ok((tasks.remove(task_id - 1), tasks))
     }
 
@@ -68,20 +212,82 @@ impl TodoManager {
         Ok(tasks)
     }
 
+    /// Write tasks back to todo.txt and done.txt in a single commit, recording `operation`
+    /// in the history.
+    /// note: `layout` is the one read with the tasks, under the same lock
+    /// note: with `ArchivePolicy::Manual` or preserved line numbers done tasks stay in todo.txt
+    pub(self) fn save_tasks(
+        &self,
+        operation: &str,
+        tasks: Vec<(usize, Task)>,
+        layout: &FileLayout,
+    ) -> Result<()> {
+        let (todos, dones) = match self.effective_archive_policy() {
+            ArchivePolicy::Auto => self.split_tasks_todo_and_done(tasks)?,
+            ArchivePolicy::Manual => {
+                crate::commands::archive::split_tasks_by_file(tasks, layout.done_ids())
+            }
+        };
+        self.write_todo_and_done(operation, todos, dones, layout)
+    }
+
+    /// note: in lenient mode the lines that could not be parsed and the blank lines are kept
+    pub(self) fn write_todo_and_done(
+        &self,
+        operation: &str,
+        todos: Vec<(usize, Task)>,
+        dones: Vec<(usize, Task)>,
+        layout: &FileLayout,
+    ) -> Result<()> {
+        let before = self.snapshot()?;
+        self.storage.save(&[
+            (
+                self.todo_file.clone(),
+                crate::commands::file_lines(&todos, &layout.todo_kept, layout.todo_ids()),
+            ),
+            (
+                self.done_file.clone(),
+                crate::commands::file_lines(&dones, &layout.done_kept, layout.done_ids()),
+            ),
+        ])?;
+        self.record(operation, before)
+    }
+
+    /// Tasks of the file `name` in the storage, and in lenient mode the lines that were skipped
+    /// and the lines to keep when rewriting it.
+    /// note: with preserved line numbers blank lines are read as placeholders
+    pub(crate) fn read_tasks(&self, name: &str, lenient: bool) -> Result<ReadTasksResult> {
+        let lines = self.storage.load(name)?;
+        let blank = self.preserve_line_numbers;
+        if lenient {
+            Ok(crate::commands::parse_tasks_lenient(
+                &self.storage.path(name),
+                &lines,
+                blank,
+            ))
+        } else {
+            Ok((
+                crate::commands::parse_tasks(&lines, blank)?,
+                Vec::new(),
+                Vec::new(),
+            ))
+        }
+    }
+
     /// Split tasks into two groups: todo and done
     pub(self) fn split_tasks_todo_and_done(
         &self,
         tasks: Vec<(usize, Task)>,
-    ) -> Result<(Vec<Task>, Vec<Task>)> {
+    ) -> Result<SplitTasksResult> {
         {
             #[cfg(not(feature = "parallel"))]
             {
                 let todos = tasks
                     .iter()
                     .cloned()
-                    .filter_map(|(_, t)| {
+                    .filter_map(|(idx, t)| {
                         if t.state == TaskState::Todo {
-                            Some(t)
+                            Some((idx, t))
                         } else {
                             None
                         }
@@ -89,9 +295,9 @@ impl TodoManager {
                     .collect::<Vec<_>>();
                 let dones = tasks
                     .into_iter()
-                    .filter_map(|(_, t)| {
+                    .filter_map(|(idx, t)| {
                         if t.state == TaskState::Done {
-                            Some(t)
+                            Some((idx, t))
                         } else {
                             None
                         }
@@ -106,9 +312,9 @@ impl TodoManager {
                 let todos = tasks
                     .par_iter()
                     .cloned()
-                    .filter_map(|(_, t)| {
+                    .filter_map(|(idx, t)| {
                         if t.state == TaskState::Todo {
-                            Some(t)
+                            Some((idx, t))
                         } else {
                             None
                         }
@@ -116,9 +322,9 @@ impl TodoManager {
                     .collect::<Vec<_>>();
                 let dones = tasks
                     .into_par_iter()
-                    .filter_map(|(_, t)| {
+                    .filter_map(|(idx, t)| {
                         if t.state == TaskState::Done {
-                            Some(t)
+                            Some((idx, t))
                         } else {
                             None
                         }
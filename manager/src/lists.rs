@@ -0,0 +1,309 @@
+use crate::{Diagnostic, Filter, Order, TodoManager};
+use todo_txt_model::prelude::*;
+
+/// Name of the list kept in todo.txt and done.txt.
+pub const DEFAULT_LIST: &str = "todo";
+
+/// Tasks of every list with the name of their list, and the lines that can not be parsed.
+pub type ListAllWithDiagnostics = (Vec<(String, usize, Task)>, Vec<Diagnostic>);
+
+/// Names of the files of a list in the data directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ListFiles {
+    pub(crate) todo: String,
+    pub(crate) done: String,
+    pub(crate) report: String,
+    pub(crate) history: String,
+}
+
+impl ListFiles {
+    /// `todo.txt`, `done.txt` and `report.txt` for the default list, `work.txt`,
+    /// `work.done.txt` and `work.report.txt` for `work`.
+    pub(crate) fn new(name: &str) -> Result<Self> {
+        check_list_name(name)?;
+        if name == DEFAULT_LIST {
+            return Ok(Self {
+                todo: "todo.txt".to_string(),
+                done: "done.txt".to_string(),
+                report: "report.txt".to_string(),
+                history: ".todo.history".to_string(),
+            });
+        }
+        Ok(Self {
+            todo: format!("{}.txt", name),
+            done: format!("{}.done.txt", name),
+            report: format!("{}.report.txt", name),
+            history: format!(".{}.history", name),
+        })
+    }
+}
+
+/// A list name is made of letters, digits, `-` and `_`, and is neither `done` nor `report`
+/// whose files belong to the default list.
+pub(crate) fn check_list_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+        && name != "done"
+        && name != "report";
+    if valid {
+        Ok(())
+    } else {
+        Err(TodoTxtRsError::InvalidList(name.to_string()))
+    }
+}
+
+/// Name of the list whose todo file is `file`, e.g. `work` for work.txt.
+fn list_of_file(file: &str) -> Option<&str> {
+    file.strip_suffix(".txt")
+        .filter(|name| check_list_name(name).is_ok())
+}
+
+impl TodoManager {
+    /// Name of the active list, `todo` unless another one was selected.
+    pub fn get_list(&self) -> &str {
+        &self.list
+    }
+
+    /// The same manager working on the list `name`, which is created by the first write.
+    /// note: the default list keeps the todo, done and report files configured for it
+    pub fn for_list(&self, name: &str) -> Result<Self> {
+        if name == self.list {
+            return Ok(self.clone());
+        }
+        let files = if name == DEFAULT_LIST {
+            self.default_list.clone()
+        } else {
+            ListFiles::new(name)?
+        };
+        let mut manager = self.clone();
+        manager.list = name.to_string();
+        manager.todo_file = files.todo;
+        manager.done_file = files.done;
+        manager.report_file = files.report;
+        manager.history_file = files.history;
+        Ok(manager)
+    }
+
+    /// Names of the lists in the data directory, the default and the active list included,
+    /// the default list first and the others by name.
+    /// note: a list is found by its todo and done files, e.g. work.txt and work.done.txt, so a
+    /// lone notes.txt is not a list; `add` creates the done file of a new list
+    /// note: the files of the default and the active list are not lists of their own, e.g. a
+    /// configured archive.txt done file
+    #[tracing::instrument(parent = None, skip(self))]
+    pub fn lists(&self) -> Result<Vec<String>> {
+        let own_files = [
+            &self.default_list.todo,
+            &self.default_list.done,
+            &self.default_list.report,
+            &self.todo_file,
+            &self.done_file,
+            &self.report_file,
+        ]
+        .map(|name| self.storage.path(name));
+        let files = self.storage.names()?;
+        let mut names = files
+            .iter()
+            .filter(|file| !own_files.contains(&self.storage.path(file)))
+            .filter_map(|file| list_of_file(file))
+            .filter(|name| *name != DEFAULT_LIST)
+            .filter(|name| ListFiles::new(name).is_ok_and(|list| files.contains(&list.done)))
+            .map(str::to_string)
+            .collect::<std::collections::BTreeSet<_>>();
+        if self.list != DEFAULT_LIST {
+            names.insert(self.list.clone());
+        }
+        Ok(std::iter::once(DEFAULT_LIST.to_string())
+            .chain(names)
+            .collect())
+    }
+
+    #[cfg(feature = "async")]
+    pub async fn lists_async(&self) -> Result<Vec<String>> {
+        self.unblock(|manager| manager.lists()).await
+    }
+
+    /// Tasks of every list with the name of their list, in the order of `lists`.
+    /// note: tasks are filtered and sorted within their list, and keep the index they have
+    /// in it
+    #[tracing::instrument(parent = None, skip(self))]
+    pub fn list_all(&self, filter: Filter, order: Order) -> Result<Vec<(String, usize, Task)>> {
+        self.list_each(|manager| manager.list(filter.clone(), order.clone()))
+    }
+
+    /// Like `list_all`, skipping the lines that can not be parsed and reporting them.
+    /// note: this reads leniently regardless of `with_lenient`
+    #[tracing::instrument(parent = None, skip(self))]
+    pub fn list_all_with_diagnostics(
+        &self,
+        filter: Filter,
+        order: Order,
+    ) -> Result<ListAllWithDiagnostics> {
+        let mut diagnostics = Vec::new();
+        let tasks = self.list_each(|manager| {
+            let (tasks, skipped) = manager.list_with_diagnostics(filter.clone(), order.clone())?;
+            diagnostics.extend(skipped);
+            Ok(tasks)
+        })?;
+        Ok((tasks, diagnostics))
+    }
+
+    /// Tasks listed by `list` in every list, with the name of their list.
+    fn list_each(
+        &self,
+        mut list: impl FnMut(&TodoManager) -> Result<Vec<(usize, Task)>>,
+    ) -> Result<Vec<(String, usize, Task)>> {
+        let mut tasks = Vec::new();
+        for name in self.lists()? {
+            let listed = list(&self.for_list(&name)?)?;
+            tasks.extend(
+                listed
+                    .into_iter()
+                    .map(|(idx, task)| (name.clone(), idx, task)),
+            );
+        }
+        Ok(tasks)
+    }
+
+    #[cfg(feature = "async")]
+    pub async fn list_all_async(
+        &self,
+        filter: Filter,
+        order: Order,
+    ) -> Result<Vec<(String, usize, Task)>> {
+        self.unblock(move |manager| manager.list_all(filter, order))
+            .await
+    }
+
+    #[cfg(feature = "async")]
+    pub async fn list_all_with_diagnostics_async(
+        &self,
+        filter: Filter,
+        order: Order,
+    ) -> Result<ListAllWithDiagnostics> {
+        self.unblock(move |manager| manager.list_all_with_diagnostics(filter, order))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{MemoryStorage, Storage};
+
+    #[test]
+    fn test_list_files() {
+        assert_eq!(ListFiles::new("todo").unwrap().done, "done.txt");
+        let files = ListFiles::new("client-a").unwrap();
+        assert_eq!(
+            (
+                files.todo.as_str(),
+                files.done.as_str(),
+                files.report.as_str()
+            ),
+            ("client-a.txt", "client-a.done.txt", "client-a.report.txt")
+        );
+        for name in ["", "done", "report", "work.done", "../work", "a b"] {
+            assert!(matches!(
+                ListFiles::new(name),
+                Err(TodoTxtRsError::InvalidList(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_lists_and_list_all() {
+        let storage = MemoryStorage::new();
+        let manager = crate::TodoManagerBuilder::new()
+            .with_env(false)
+            .build()
+            .unwrap()
+            .with_storage(storage.clone())
+            .with_archive_policy(crate::ArchivePolicy::Manual);
+        storage
+            .save(&[
+                ("todo.txt".to_string(), vec!["a +home".into()]),
+                ("done.txt".to_string(), vec!["x b +home".into()]),
+                ("work.txt".to_string(), vec!["c".into(), "d +home".into()]),
+                ("work.done.txt".to_string(), vec!["x e".into()]),
+                ("home.report.txt".to_string(), Vec::new()),
+                ("notes.txt".to_string(), vec!["not a list".into()]),
+                ("notes.md".to_string(), Vec::new()),
+            ])
+            .unwrap();
+        assert_eq!(manager.lists().unwrap(), vec!["todo", "work"]);
+
+        let work = manager.for_list("work").unwrap();
+        assert_eq!(work.get_list(), "work");
+        work.add(todo_txt_serializer::from_str("f").unwrap())
+            .unwrap();
+        assert_eq!(storage.load("work.txt").unwrap().len(), 3);
+        assert_eq!(storage.load("todo.txt").unwrap().len(), 1);
+        manager
+            .for_list("home")
+            .unwrap()
+            .add(todo_txt_serializer::from_str("g").unwrap())
+            .unwrap();
+        assert_eq!(manager.lists().unwrap(), vec!["todo", "home", "work"]);
+
+        let filter = Filter {
+            project: Some(vec!["home".to_string()]),
+            ..Default::default()
+        };
+        let tasks = manager
+            .list_all(filter, Order::default())
+            .unwrap()
+            .into_iter()
+            .map(|(list, idx, _)| (list, idx))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tasks,
+            vec![
+                ("todo".to_string(), 1),
+                ("todo".to_string(), 2),
+                ("work".to_string(), 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lists_with_configured_files() {
+        let dir = std::env::temp_dir().join(format!("todo-txt-rs-lists-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        for (file, content) in [
+            ("tasks.txt", "a\n"),
+            ("archive.txt", "x b\n"),
+            ("work.txt", "c\n"),
+        ] {
+            std::fs::write(dir.join(file), content).unwrap();
+        }
+        let work = crate::TodoManagerBuilder::new()
+            .with_env(false)
+            .with_data_dir(&dir)
+            .with_todo_file("tasks.txt")
+            .with_done_file("archive.txt")
+            .with_list("work")
+            .build()
+            .unwrap();
+        assert_eq!(work.lists().unwrap(), vec!["todo", "work"]);
+        let default = work.for_list(DEFAULT_LIST).unwrap();
+        assert_eq!(default.get_todo_path(), dir.join("tasks.txt"));
+        assert_eq!(default.get_done_path(), dir.join("archive.txt"));
+        assert_eq!(default.for_list("work").unwrap().get_list(), "work");
+
+        let tasks = work
+            .list_all(Filter::default(), Order::default())
+            .unwrap()
+            .into_iter()
+            .map(|(list, _, task)| (list, todo_txt_serializer::to_string(&task)))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tasks,
+            [("todo", "a"), ("todo", "x b"), ("work", "c")]
+                .map(|(list, task)| (list.to_string(), task.to_string()))
+        );
+    }
+}
@@ -0,0 +1,437 @@
+use todo_txt_model::prelude::*;
+
+/// Boolean filter expression, e.g. `(+work or +ops) and not @waiting and pri>=B and due<today+3d`.
+///
+/// - `+project`, `@context`: the task has the project or context
+/// - `key:value`: the task has the tag with this value
+/// - `pri`, `due`, `t`, `created`, `completed` compared with `=`, `!=`, `<`, `<=`, `>`, `>=`
+/// - `state=todo` or `state=done`
+/// - any other word or `"quoted text"`: the description contains it, ignoring case
+///
+/// Terms are combined with `and`, `or`, `not` and parentheses. Adjacent terms are joined by `and`.
+/// note: `pri>=B` means B or more important, i.e. A or B
+/// note: comparisons never match tasks that do not have the compared field
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Query {
+    And(Box<Query>, Box<Query>),
+    Or(Box<Query>, Box<Query>),
+    Not(Box<Query>),
+    Project(String),
+    Context(String),
+    Tag(String, String),
+    Text(String),
+    State(TaskState),
+    Priority(Comparison, TaskPriority),
+    Date(DateField, Comparison, DateValue),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateField {
+    Due,
+    Threshold,
+    Created,
+    Completed,
+}
+
+/// A date, or a date relative to the day the query is evaluated, e.g. `today+3d` or `today-1w`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateValue {
+    Absolute(chrono::NaiveDate),
+    Relative(i64, RelativeUnit),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelativeUnit {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+impl Comparison {
+    fn test<T: Ord>(&self, a: T, b: T) -> bool {
+        match self {
+            Self::Eq => a == b,
+            Self::Ne => a != b,
+            Self::Lt => a < b,
+            Self::Le => a <= b,
+            Self::Gt => a > b,
+            Self::Ge => a >= b,
+        }
+    }
+}
+
+impl DateValue {
+    fn resolve(&self, today: chrono::NaiveDate) -> Option<chrono::NaiveDate> {
+        match *self {
+            Self::Absolute(date) => Some(date),
+            Self::Relative(amount, unit) => {
+                let days = |n: i64| chrono::Duration::try_days(n);
+                let months = |n: i64| {
+                    let m = chrono::Months::new(u32::try_from(n.unsigned_abs()).ok()?);
+                    if n < 0 {
+                        today.checked_sub_months(m)
+                    } else {
+                        today.checked_add_months(m)
+                    }
+                };
+                match unit {
+                    RelativeUnit::Day => today.checked_add_signed(days(amount)?),
+                    RelativeUnit::Week => today.checked_add_signed(days(amount.checked_mul(7)?)?),
+                    RelativeUnit::Month => months(amount),
+                    RelativeUnit::Year => months(amount.checked_mul(12)?),
+                }
+            }
+        }
+    }
+}
+
+impl Query {
+    pub fn matches(&self, task: &Task, today: chrono::NaiveDate) -> bool {
+        match self {
+            Self::And(a, b) => a.matches(task, today) && b.matches(task, today),
+            Self::Or(a, b) => a.matches(task, today) || b.matches(task, today),
+            Self::Not(q) => !q.matches(task, today),
+            Self::Project(p) => task.description.project.contains(p),
+            Self::Context(c) => task.description.context.contains(c),
+            Self::Tag(k, v) => task.description.tags.get(k) == Some(v.as_str()),
+            Self::Text(text) => task
+                .description
+                .value
+                .to_lowercase()
+                .contains(&text.to_lowercase()),
+            Self::State(state) => task.state == *state,
+            // A is the most important priority and the smallest one
+            Self::Priority(cmp, p) => task.priority.is_some_and(|t| cmp.test(*p, t)),
+            Self::Date(field, cmp, value) => {
+                let date = match field {
+                    DateField::Due => task.due_date(),
+                    DateField::Threshold => task.threshold_date(),
+                    DateField::Created => task.created_date,
+                    DateField::Completed => task.completed_date,
+                };
+                match (date, value.resolve(today)) {
+                    (Some(date), Some(value)) => cmp.test(date, value),
+                    _ => false,
+                }
+            }
+        }
+    }
+}
+
+impl std::str::FromStr for Query {
+    type Err = TodoTxtRsError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let tokens = tokenize(s)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let query = parser.parse_or()?;
+        match parser.tokens.get(parser.pos) {
+            None => Ok(query),
+            Some(token) => Err(invalid(format!("unexpected {}", token))),
+        }
+    }
+}
+
+fn invalid(message: impl Into<String>) -> TodoTxtRsError {
+    TodoTxtRsError::InvalidQuery(message.into())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Open,
+    Close,
+    Word(String),
+    Quoted(String),
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Open => write!(f, "'('"),
+            Self::Close => write!(f, "')'"),
+            Self::Word(w) => write!(f, "'{}'", w),
+            Self::Quoted(q) => write!(f, "\"{}\"", q),
+        }
+    }
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = s.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::Open);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::Close);
+            }
+            '"' => {
+                chars.next();
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => text.push(c),
+                        None => return Err(invalid("unterminated quote")),
+                    }
+                }
+                tokens.push(Token::Quoted(text));
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || matches!(c, '(' | ')' | '"') {
+                        break;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Word(word));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.tokens.get(self.pos), Some(Token::Word(w)) if w.eq_ignore_ascii_case(keyword))
+    }
+
+    fn parse_or(&mut self) -> Result<Query> {
+        let mut query = self.parse_and()?;
+        while self.peek_keyword("or") {
+            self.pos += 1;
+            query = Query::Or(Box::new(query), Box::new(self.parse_and()?));
+        }
+        Ok(query)
+    }
+
+    fn parse_and(&mut self) -> Result<Query> {
+        let mut query = self.parse_not()?;
+        loop {
+            if self.peek_keyword("and") {
+                self.pos += 1;
+            } else if self.peek_keyword("or")
+                || matches!(self.tokens.get(self.pos), None | Some(Token::Close))
+            {
+                return Ok(query);
+            }
+            query = Query::And(Box::new(query), Box::new(self.parse_not()?));
+        }
+    }
+
+    fn parse_not(&mut self) -> Result<Query> {
+        if self.peek_keyword("not") {
+            self.pos += 1;
+            return Ok(Query::Not(Box::new(self.parse_not()?)));
+        }
+        self.parse_term()
+    }
+
+    fn parse_term(&mut self) -> Result<Query> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or_else(|| invalid("unexpected end of query"))?;
+        self.pos += 1;
+        match token {
+            Token::Open => {
+                let query = self.parse_or()?;
+                match self.tokens.get(self.pos) {
+                    Some(Token::Close) => {
+                        self.pos += 1;
+                        Ok(query)
+                    }
+                    _ => Err(invalid("missing ')'")),
+                }
+            }
+            Token::Close => Err(invalid("unexpected ')'")),
+            Token::Quoted(text) => Ok(Query::Text(text)),
+            Token::Word(word) => parse_word(&word),
+        }
+    }
+}
+
+fn parse_word(word: &str) -> Result<Query> {
+    if let Some(project) = word.strip_prefix('+').filter(|p| !p.is_empty()) {
+        return Ok(Query::Project(project.to_string()));
+    }
+    if let Some(context) = word.strip_prefix('@').filter(|c| !c.is_empty()) {
+        return Ok(Query::Context(context.to_string()));
+    }
+    if let Some((key, cmp, value)) = split_comparison(word) {
+        return parse_comparison(key, cmp, value);
+    }
+    if let Some((key, value)) = word.split_once(':') {
+        if !key.is_empty() && !value.is_empty() {
+            return Ok(Query::Tag(key.to_string(), value.to_string()));
+        }
+    }
+    Ok(Query::Text(word.to_string()))
+}
+
+fn split_comparison(word: &str) -> Option<(&str, Comparison, &str)> {
+    const OPERATORS: [(&str, Comparison); 6] = [
+        ("!=", Comparison::Ne),
+        ("<=", Comparison::Le),
+        (">=", Comparison::Ge),
+        ("=", Comparison::Eq),
+        ("<", Comparison::Lt),
+        (">", Comparison::Gt),
+    ];
+    let start = word.find(['=', '!', '<', '>'])?;
+    let (key, rest) = word.split_at(start);
+    OPERATORS
+        .iter()
+        .find_map(|(op, cmp)| rest.strip_prefix(op).map(|value| (key, *cmp, value)))
+}
+
+fn parse_comparison(key: &str, cmp: Comparison, value: &str) -> Result<Query> {
+    let field = match key.to_ascii_lowercase().as_str() {
+        "pri" | "priority" => {
+            let mut chars = value.chars();
+            return match (chars.next(), chars.next()) {
+                (Some(p), None) if p.is_ascii_alphabetic() => {
+                    Ok(Query::Priority(cmp, TaskPriority::from(p)))
+                }
+                _ => Err(invalid(format!("invalid priority '{}'", value))),
+            };
+        }
+        "state" => {
+            let state = match value.to_ascii_lowercase().as_str() {
+                "todo" => TaskState::Todo,
+                "done" => TaskState::Done,
+                _ => return Err(invalid(format!("invalid state '{}'", value))),
+            };
+            return match cmp {
+                Comparison::Eq => Ok(Query::State(state)),
+                Comparison::Ne => Ok(Query::Not(Box::new(Query::State(state)))),
+                _ => Err(invalid("state can only be compared with = or !=")),
+            };
+        }
+        "due" => DateField::Due,
+        "t" | "threshold" => DateField::Threshold,
+        "created" => DateField::Created,
+        "completed" => DateField::Completed,
+        _ => return Err(invalid(format!("unknown field '{}'", key))),
+    };
+    Ok(Query::Date(field, cmp, parse_date_value(value)?))
+}
+
+fn parse_date_value(value: &str) -> Result<DateValue> {
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(value, DATE_FORMAT) {
+        return Ok(DateValue::Absolute(date));
+    }
+    let error = || invalid(format!("invalid date '{}'", value));
+    let lower = value.to_ascii_lowercase();
+    let offset = match lower.as_str() {
+        "today" => return Ok(DateValue::Relative(0, RelativeUnit::Day)),
+        "tomorrow" => return Ok(DateValue::Relative(1, RelativeUnit::Day)),
+        "yesterday" => return Ok(DateValue::Relative(-1, RelativeUnit::Day)),
+        s => s.strip_prefix("today").ok_or_else(error)?,
+    };
+    let unit = match offset.chars().last() {
+        Some('d') => RelativeUnit::Day,
+        Some('w') => RelativeUnit::Week,
+        Some('m') => RelativeUnit::Month,
+        Some('y') => RelativeUnit::Year,
+        _ => return Err(error()),
+    };
+    let amount = offset[..offset.len() - 1]
+        .strip_prefix('+')
+        .unwrap_or(&offset[..offset.len() - 1])
+        .parse::<i64>()
+        .map_err(|_| error())?;
+    Ok(DateValue::Relative(amount, unit))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> chrono::NaiveDate {
+        chrono::NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn task(line: &str) -> Task {
+        todo_txt_serializer::from_str(line).unwrap()
+    }
+
+    #[test]
+    fn test_parse_query() {
+        let query = "(+work or +ops) and not @waiting pri>=B"
+            .parse::<Query>()
+            .unwrap();
+        assert_eq!(
+            query,
+            Query::And(
+                Box::new(Query::And(
+                    Box::new(Query::Or(
+                        Box::new(Query::Project("work".to_string())),
+                        Box::new(Query::Project("ops".to_string())),
+                    )),
+                    Box::new(Query::Not(Box::new(Query::Context("waiting".to_string())))),
+                )),
+                Box::new(Query::Priority(Comparison::Ge, TaskPriority::B)),
+            )
+        );
+        assert_eq!(
+            "due<today+3d".parse::<Query>().unwrap(),
+            Query::Date(
+                DateField::Due,
+                Comparison::Lt,
+                DateValue::Relative(3, RelativeUnit::Day)
+            )
+        );
+        assert!("(+work".parse::<Query>().is_err());
+        assert!("+work or".parse::<Query>().is_err());
+        assert!("\"invoice".parse::<Query>().is_err());
+        assert!("pri>=1".parse::<Query>().is_err());
+        assert!("due<someday".parse::<Query>().is_err());
+        assert!("foo<1".parse::<Query>().is_err());
+    }
+
+    #[test]
+    fn test_query_matches() {
+        let today = date(2021, 1, 10);
+        let query = "(+work or +ops) and not @waiting and pri>=B and due<today+3d and \"invoice\""
+            .parse::<Query>()
+            .unwrap();
+        assert!(query.matches(&task("(A) send Invoice +ops due:2021-01-12"), today));
+        assert!(query.matches(&task("(B) invoice +work due:2021-01-01"), today));
+        assert!(!query.matches(&task("(C) invoice +work due:2021-01-01"), today));
+        assert!(!query.matches(&task("(A) invoice +work @waiting due:2021-01-01"), today));
+        assert!(!query.matches(&task("(A) invoice +work due:2021-01-13"), today));
+        assert!(!query.matches(&task("(A) invoice +work"), today));
+        assert!(!query.matches(&task("(A) invoice +home due:2021-01-01"), today));
+
+        let query = "state=done or rec:1w".parse::<Query>().unwrap();
+        assert!(query.matches(&task("x done"), today));
+        assert!(query.matches(&task("water plants rec:1w"), today));
+        assert!(!query.matches(&task("water plants"), today));
+    }
+}
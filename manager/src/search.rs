@@ -0,0 +1,278 @@
+use todo_txt_model::prelude::*;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+#[clap(rename_all = "snake_case")]
+pub enum SearchMode {
+    /// The pattern appears anywhere.
+    #[default]
+    Substring,
+    /// The pattern appears as a whole word.
+    Word,
+    /// The pattern is a regular expression.
+    Regex,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SearchOptions {
+    pub mode: SearchMode,
+    pub case_sensitive: bool,
+    /// Also search projects, contexts and tags, not only the description text.
+    pub all_fields: bool,
+}
+
+/// Text search over task descriptions.
+#[derive(Debug, Clone)]
+pub struct TextSearch {
+    regex: regex::Regex,
+    /// Whether the match is the first group, between the characters bounding the word
+    word: bool,
+    all_fields: bool,
+}
+
+impl TextSearch {
+    pub fn new(pattern: &str, options: SearchOptions) -> Result<Self> {
+        let pattern = match options.mode {
+            SearchMode::Substring => regex::escape(pattern),
+            SearchMode::Word => word_pattern(pattern),
+            SearchMode::Regex => pattern.to_string(),
+        };
+        let regex = regex::RegexBuilder::new(&pattern)
+            .case_insensitive(!options.case_sensitive)
+            .build()
+            .map_err(|e| TodoTxtRsError::InvalidPattern(e.to_string()))?;
+        Ok(Self {
+            regex,
+            word: options.mode == SearchMode::Word,
+            all_fields: options.all_fields,
+        })
+    }
+
+    pub fn matches(&self, task: &Task) -> bool {
+        let description = &task.description;
+        self.regex.is_match(&description.value)
+            || (self.all_fields
+                && (description.project.iter().any(|p| self.regex.is_match(p))
+                    || description.context.iter().any(|c| self.regex.is_match(c))
+                    || description
+                        .tags
+                        .iter()
+                        .any(|(k, v)| self.regex.is_match(&format!("{}:{}", k, v)))))
+    }
+
+    /// Byte ranges of the matches in `text`.
+    pub fn find_ranges(&self, text: &str) -> Vec<std::ops::Range<usize>> {
+        let mut ranges = Vec::new();
+        let mut start = 0;
+        while let Some(captures) = self.regex.captures_at(text, start) {
+            let Some(m) = captures.get(usize::from(self.word)) else {
+                break;
+            };
+            if !m.is_empty() {
+                ranges.push(m.range());
+            }
+            // the character after a word can start the next one
+            start = match text[m.end()..].chars().next() {
+                Some(c) if m.is_empty() => m.end() + c.len_utf8(),
+                None if m.is_empty() => break,
+                _ => m.end(),
+            };
+        }
+        ranges
+    }
+
+    /// Byte ranges of the matches in `todo_txt_serializer::to_string(task)`, for highlighting.
+    /// note: the description is searched as it is written in the line, from its first word,
+    /// and only the parts of the matches in the fields that `matches` searches are kept
+    pub fn find_task_ranges(&self, task: &Task) -> Vec<std::ops::Range<usize>> {
+        let line = todo_txt_serializer::to_string(task);
+        let searched = self.searched_ranges(task, &line);
+        let Some(offset) = searched.first().map(|r| r.start) else {
+            return Vec::new();
+        };
+        self.find_ranges(&line[offset..])
+            .into_iter()
+            .flat_map(|found| {
+                let found = found.start + offset..found.end + offset;
+                searched.iter().filter_map(move |field| {
+                    let range = found.start.max(field.start)..found.end.min(field.end);
+                    (range.start < range.end).then_some(range)
+                })
+            })
+            .collect()
+    }
+
+    /// Byte ranges of the line of `task` that `matches` searches: the description words, and
+    /// with `all_fields` the projects, contexts and tags.
+    /// note: the separators between two searched tokens are part of the range
+    fn searched_ranges(&self, task: &Task, line: &str) -> Vec<std::ops::Range<usize>> {
+        let description = &task.description;
+        let prefix = usize::from(task.is_done())
+            + usize::from(task.priority.is_some())
+            + usize::from(task.completed_date.is_some())
+            + usize::from(task.created_date.is_some());
+        let mut ranges: Vec<std::ops::Range<usize>> = Vec::new();
+        let mut previous_searched = false;
+        let mut start = 0;
+        for (index, token) in line.split(todo_txt_serializer::TOKEN_SEPARATOR).enumerate() {
+            let range = start..start + token.len();
+            start = range.end + todo_txt_serializer::TOKEN_SEPARATOR.len_utf8();
+            if index < prefix {
+                continue;
+            }
+            let field = if let Some(p) = token.strip_prefix('+') {
+                description.project.iter().any(|x| x == p)
+            } else if let Some(c) = token.strip_prefix('@') {
+                description.context.iter().any(|x| x == c)
+            } else {
+                token
+                    .split_once(':')
+                    .is_some_and(|(k, v)| description.tags.get(k) == Some(v))
+            };
+            let searched = !field || self.all_fields;
+            match ranges.last_mut() {
+                Some(last) if searched && previous_searched => last.end = range.end,
+                _ if searched => ranges.push(range),
+                _ => {}
+            }
+            previous_searched = searched;
+        }
+        ranges
+    }
+}
+
+/// Regex of `pattern` as a whole word: an edge that is a word character must be next to a
+/// non-word character, any other edge next to whitespace, e.g. `c++` in `learn c++ now`.
+/// note: `\b` is not used, it never matches next to a pattern edge like `+`
+fn word_pattern(pattern: &str) -> String {
+    let is_word = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+    let before = if is_word(pattern.chars().next()) {
+        r"(?:^|\W)"
+    } else {
+        r"(?:^|\s)"
+    };
+    let after = if is_word(pattern.chars().next_back()) {
+        r"(?:\W|$)"
+    } else {
+        r"(?:\s|$)"
+    };
+    format!("{}({}){}", before, regex::escape(pattern), after)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn task(line: &str) -> Task {
+        todo_txt_serializer::from_str(line).unwrap()
+    }
+
+    #[test]
+    fn test_search_modes() {
+        let search = |pattern: &str, mode: SearchMode| {
+            TextSearch::new(
+                pattern,
+                SearchOptions {
+                    mode,
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+        };
+        let t = task("Send invoices to ACME +work @mail");
+        assert!(search("INVOICE", SearchMode::Substring).matches(&t));
+        assert!(!search("invoice", SearchMode::Word).matches(&t));
+        assert!(search("invoices", SearchMode::Word).matches(&t));
+        assert!(search(r"^send .* acme$", SearchMode::Regex).matches(&t));
+        assert!(!search("work", SearchMode::Substring).matches(&t));
+        assert!(TextSearch::new(
+            "(",
+            SearchOptions {
+                mode: SearchMode::Regex,
+                ..Default::default()
+            }
+        )
+        .is_err());
+
+        let all_fields = TextSearch::new(
+            "work",
+            SearchOptions {
+                all_fields: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(all_fields.matches(&t));
+        let case_sensitive = TextSearch::new(
+            "acme",
+            SearchOptions {
+                case_sensitive: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(!case_sensitive.matches(&t));
+    }
+
+    #[test]
+    fn test_find_ranges() {
+        let search = TextSearch::new("ab", SearchOptions::default()).unwrap();
+        assert_eq!(search.find_ranges("xAbyab"), vec![1..3, 4..6]);
+        let word = |pattern: &str| {
+            TextSearch::new(
+                pattern,
+                SearchOptions {
+                    mode: SearchMode::Word,
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+        };
+        assert_eq!(word("a").find_ranges("a a ab"), vec![0..1, 2..3]);
+        assert_eq!(word("c++").find_ranges("learn c++ now"), vec![6..9]);
+        assert!(word("c++").matches(&task("learn c++")));
+        assert!(!word("c+").matches(&task("learn c++")));
+    }
+
+    #[test]
+    fn test_find_task_ranges() {
+        let t = task("(A) 2021-01-01 send work +work report @work due:2021-01-05");
+        let line = todo_txt_serializer::to_string(&t);
+        let highlighted = |search: TextSearch| {
+            search
+                .find_task_ranges(&t)
+                .into_iter()
+                .map(|r| &line[r])
+                .collect::<Vec<_>>()
+        };
+        let options = |mode: SearchMode, all_fields: bool| SearchOptions {
+            mode,
+            all_fields,
+            ..Default::default()
+        };
+
+        let search = TextSearch::new("work", options(SearchMode::Substring, false)).unwrap();
+        assert_eq!(search.find_task_ranges(&t), vec![9 + 11..9 + 15]);
+        assert_eq!(highlighted(search), vec!["work"]);
+        let search = TextSearch::new("work", options(SearchMode::Substring, true)).unwrap();
+        assert_eq!(highlighted(search), vec!["work", "work", "work"]);
+        let search = TextSearch::new("2021", options(SearchMode::Substring, true)).unwrap();
+        assert_eq!(highlighted(search), vec!["2021"]);
+        let search = TextSearch::new("^send", options(SearchMode::Regex, false)).unwrap();
+        assert_eq!(highlighted(search), vec!["send"]);
+        let search = TextSearch::new("send work", options(SearchMode::Substring, false)).unwrap();
+        assert_eq!(highlighted(search), vec!["send work"]);
+        // the project in a match is not highlighted
+        let search = TextSearch::new("work +work r", options(SearchMode::Substring, true)).unwrap();
+        assert_eq!(highlighted(search), vec!["work +work r"]);
+        let search =
+            TextSearch::new("work +work r", options(SearchMode::Substring, false)).unwrap();
+        assert_eq!(highlighted(search), vec!["work", "r"]);
+
+        let t = task("call  mom +home");
+        let line = todo_txt_serializer::to_string(&t);
+        assert_eq!(line, "call  mom +home");
+        let search = TextSearch::new("l  m", options(SearchMode::Substring, false)).unwrap();
+        assert!(search.matches(&t));
+        assert_eq!(search.find_task_ranges(&t), vec![3..7]);
+    }
+}
@@ -0,0 +1,173 @@
+use crate::commands::is_placeholder;
+use crate::{Filter, TaskRef, TaskSelector, TodoManager};
+use todo_txt_model::prelude::*;
+
+/// Tasks a bulk operation applies to.
+#[derive(Debug, Clone)]
+pub enum Selection {
+    /// Indexes, uids and ranges of indexes.
+    Tasks(Vec<TaskSelector>),
+    /// Every task matching the filter.
+    Filter(Box<Filter>),
+}
+
+impl From<Vec<TaskSelector>> for Selection {
+    fn from(value: Vec<TaskSelector>) -> Self {
+        Self::Tasks(value)
+    }
+}
+
+impl From<Vec<TaskRef>> for Selection {
+    fn from(value: Vec<TaskRef>) -> Self {
+        Self::Tasks(value.into_iter().map(TaskSelector::Ref).collect())
+    }
+}
+
+impl From<TaskRef> for Selection {
+    fn from(value: TaskRef) -> Self {
+        Self::Tasks(vec![TaskSelector::Ref(value)])
+    }
+}
+
+impl From<usize> for Selection {
+    fn from(value: usize) -> Self {
+        Self::Tasks(vec![TaskSelector::from(value)])
+    }
+}
+
+impl From<TaskSelector> for Selection {
+    fn from(value: TaskSelector) -> Self {
+        Self::Tasks(vec![value])
+    }
+}
+
+impl From<Filter> for Selection {
+    fn from(value: Filter) -> Self {
+        Self::Filter(Box::new(value))
+    }
+}
+
+impl Selection {
+    /// 0-based positions of the selected tasks in `tasks`, in order and without duplicates.
+    /// note: fails with `InvalidIndex` or `NotFound` when any referenced task does not exist,
+    /// so nothing is changed
+    /// note: ranges skip the blank lines kept by `with_preserve_line_numbers`
+    pub(crate) fn positions(
+        &self,
+        tasks: &[(usize, Task)],
+        today: chrono::NaiveDate,
+    ) -> Result<Vec<usize>> {
+        let index = |task_id: usize| {
+            if task_id == 0 || task_id > tasks.len() {
+                return Err(TodoTxtRsError::InvalidIndex);
+            }
+            Ok(task_id - 1)
+        };
+        let mut positions = match self {
+            Self::Tasks(selectors) => {
+                let mut positions = Vec::new();
+                for selector in selectors {
+                    match selector {
+                        TaskSelector::Ref(TaskRef::Index(task_id)) => {
+                            let pos = index(*task_id)?;
+                            if is_placeholder(&tasks[pos].1) {
+                                return Err(TodoTxtRsError::NotFound);
+                            }
+                            positions.push(pos)
+                        }
+                        TaskSelector::Ref(TaskRef::Uid(uid)) => positions.push(
+                            tasks
+                                .iter()
+                                .position(|(_, t)| t.uid() == Some(uid.as_str()))
+                                .ok_or(TodoTxtRsError::NotFound)?,
+                        ),
+                        TaskSelector::Range(start, end) => {
+                            index(*end)?;
+                            positions.extend(
+                                (index(*start)?..*end)
+                                    .filter(|pos| !is_placeholder(&tasks[*pos].1)),
+                            );
+                        }
+                    }
+                }
+                positions
+            }
+            Self::Filter(filter) => tasks
+                .iter()
+                .enumerate()
+                .filter(|(_, (_, t))| filter.matches(t, today))
+                .map(|(pos, _)| pos)
+                .collect(),
+        };
+        positions.sort_unstable();
+        positions.dedup();
+        Ok(positions)
+    }
+}
+
+impl TodoManager {
+    /// Let `edit` change the tasks in a single read-modify-write cycle, and save them when it
+    /// returns changed tasks, labelled by `operation` and their ids in the history.
+    pub(crate) fn edit_many(
+        &self,
+        operation: &str,
+        edit: impl FnOnce(&mut Vec<(usize, Task)>) -> Result<Vec<(usize, Task)>>,
+    ) -> Result<Vec<(usize, Task)>> {
+        let _lock = self.lock()?;
+        let (mut tasks, layout) = self.read_for_write()?;
+        let changed = edit(&mut tasks)?;
+        if !changed.is_empty() {
+            self.save_tasks(&operation_label(operation, &changed), tasks, &layout)?;
+        }
+        Ok(changed)
+    }
+}
+
+/// Operation label for the history, listing the ids of the changed tasks.
+pub(crate) fn operation_label(operation: &str, changed: &[(usize, Task)]) -> String {
+    changed
+        .iter()
+        .fold(operation.to_string(), |mut label, (id, _)| {
+            label.push_str(&format!(" {}", id));
+            label
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn tasks(lines: &[&str]) -> Vec<(usize, Task)> {
+        lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| (i + 1, todo_txt_serializer::from_str(line).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_selection_positions() {
+        let today = chrono::NaiveDate::from_ymd_opt(2021, 1, 10).unwrap();
+        let tasks = tasks(&["a +acme", "b", "c +acme id:x1", "d", "e"]);
+        let select = |selection: Selection| selection.positions(&tasks, today);
+
+        let selectors = ["5", "2-3", "x1", "1"]
+            .iter()
+            .map(|s| s.parse::<TaskSelector>().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(select(selectors.into()).unwrap(), vec![0, 1, 2, 4]);
+        let filter = Filter {
+            project: Some(vec!["acme".to_string()]),
+            ..Default::default()
+        };
+        assert_eq!(select(filter.into()).unwrap(), vec![0, 2]);
+        assert!(matches!(
+            select(TaskSelector::Range(4, 6).into()),
+            Err(TodoTxtRsError::InvalidIndex)
+        ));
+        assert!(matches!(
+            select(vec![TaskRef::Uid("nope".to_string())].into()),
+            Err(TodoTxtRsError::NotFound)
+        ));
+    }
+}
@@ -0,0 +1,56 @@
+mod file;
+mod memory;
+
+pub use file::FileStorage;
+pub use memory::MemoryStorage;
+
+use todo_txt_model::prelude::*;
+
+/// Where `TodoManager` keeps its files: todo.txt, done.txt and the history.
+///
+/// Files are named relative to the storage, e.g. `todo.txt`, and hold lines without
+/// their line endings.
+pub trait Storage: std::fmt::Debug + Send + Sync {
+    /// Lines of the file `name`, no lines when it does not exist.
+    fn load(&self, name: &str) -> Result<Vec<String>>;
+
+    /// Replace the lines of several files so that either all of them or none of them change.
+    fn save(&self, files: &[(String, Vec<String>)]) -> Result<()>;
+
+    /// Exclusive access for a read-modify-write cycle, waiting up to `timeout` for another
+    /// holder before failing with `LockTimeout`.
+    /// note: a `save` interrupted by a crash is completed before the lock is returned
+    fn lock(&self, timeout: std::time::Duration) -> Result<StorageLock>;
+
+    /// Whether a `save` was interrupted and has to be completed by `lock` before reading.
+    fn needs_recovery(&self) -> bool {
+        false
+    }
+
+    /// Names of the files in the storage, in no particular order.
+    /// note: a storage that can not list its files has none
+    fn names(&self) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+
+    /// Path of the file `name`, for messages.
+    fn path(&self, name: &str) -> std::path::PathBuf {
+        std::path::PathBuf::from(name)
+    }
+}
+
+/// Exclusive access to a storage, released when dropped.
+pub struct StorageLock(#[allow(dead_code)] Box<dyn std::any::Any + Send>);
+
+impl StorageLock {
+    /// Wrap whatever releases the lock when dropped, e.g. a locked file.
+    pub fn new(guard: impl std::any::Any + Send) -> Self {
+        Self(Box::new(guard))
+    }
+}
+
+impl std::fmt::Debug for StorageLock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StorageLock").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,293 @@
+use super::{Storage, StorageLock};
+use todo_txt_model::prelude::*;
+
+/// Files in a directory, written atomically through a journal and guarded by a lock file.
+#[derive(Debug, Clone)]
+pub struct FileStorage {
+    dir: std::path::PathBuf,
+    journal_file: std::path::PathBuf,
+    lock_file: std::path::PathBuf,
+}
+
+impl FileStorage {
+    /// Store files in `dir`, next to the `.todo.journal` and `.todo.lock` files.
+    /// note: absolute file names are used as they are, e.g. for a done.txt elsewhere
+    pub fn new(dir: impl Into<std::path::PathBuf>) -> Self {
+        let dir = dir.into();
+        Self {
+            journal_file: dir.join(".todo.journal"),
+            lock_file: dir.join(".todo.lock"),
+            dir,
+        }
+    }
+
+    fn paths(&self, files: &[(String, Vec<String>)]) -> Vec<(std::path::PathBuf, Vec<String>)> {
+        files
+            .iter()
+            .map(|(name, lines)| (self.path(name), lines.clone()))
+            .collect()
+    }
+}
+
+impl Storage for FileStorage {
+    /// note: invalid UTF-8 is replaced, so the rest of the line can still be read
+    fn load(&self, name: &str) -> Result<Vec<String>> {
+        read_lines(&self.path(name))
+    }
+
+    fn save(&self, files: &[(String, Vec<String>)]) -> Result<()> {
+        write_lines_all(&self.journal_file, &self.paths(files))
+    }
+
+    fn lock(&self, timeout: std::time::Duration) -> Result<StorageLock> {
+        let lock = lock_file(&self.lock_file, timeout)?;
+        recover(&self.journal_file)?;
+        Ok(StorageLock::new(lock))
+    }
+
+    fn needs_recovery(&self) -> bool {
+        self.journal_file.exists()
+    }
+
+    fn names(&self) -> Result<Vec<String>> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut names = Vec::new();
+        for entry in entries {
+            let entry = entry?;
+            if entry.file_type()?.is_file() {
+                if let Ok(name) = entry.file_name().into_string() {
+                    names.push(name);
+                }
+            }
+        }
+        Ok(names)
+    }
+
+    fn path(&self, name: &str) -> std::path::PathBuf {
+        self.dir.join(name)
+    }
+}
+
+fn read_lines(file: &std::path::Path) -> Result<Vec<String>> {
+    match std::fs::read(file) {
+        Ok(content) => Ok(String::from_utf8_lossy(&content)
+            .lines()
+            .map(str::to_string)
+            .collect()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+const LOCK_RETRY_INTERVAL: std::time::Duration = std::time::Duration::from_millis(50);
+
+#[tracing::instrument(parent = None, skip(writer))]
+fn write_line<W: std::io::Write>(writer: &mut std::io::BufWriter<W>, line: &str) -> Result<()> {
+    use std::io::Write;
+    writeln!(writer, "{}", line)?;
+    Ok(())
+}
+
+/// Write raw lines to several files so that either all of them or none of them are replaced.
+#[tracing::instrument(parent = None, skip(files))]
+fn write_lines_all(
+    journal: &std::path::Path,
+    files: &[(std::path::PathBuf, Vec<String>)],
+) -> Result<()> {
+    let mut renames = Vec::new();
+    for (file, lines) in files {
+        let tmp = temp_file_of(file);
+        write_lines_to_temp(&tmp, file, lines)?;
+        renames.push((tmp, file.to_path_buf()));
+    }
+
+    let journal_tmp = temp_file_of(journal);
+    std::fs::write(&journal_tmp, journal_content(&renames))?;
+    std::fs::File::open(&journal_tmp)?.sync_all()?;
+    std::fs::rename(&journal_tmp, journal)?;
+    sync_parent_dir(journal)?;
+    tracing::debug!("journal written: {:?}", renames);
+
+    for (tmp, file) in &renames {
+        std::fs::rename(tmp, file)?;
+        sync_parent_dir(file)?;
+    }
+    std::fs::remove_file(journal)?;
+    Ok(())
+}
+
+/// Complete the renames of an interrupted `write_tasks_all`.
+#[tracing::instrument(parent = None)]
+fn recover(journal: &std::path::Path) -> Result<()> {
+    if !journal.exists() {
+        return Ok(());
+    }
+    tracing::warn!("recovering interrupted write from {:?}", journal);
+    for (tmp, file) in parse_journal(&std::fs::read_to_string(journal)?) {
+        if tmp.exists() {
+            tracing::debug!("rename {:?} to {:?}", tmp, file);
+            std::fs::rename(&tmp, &file)?;
+            sync_parent_dir(&file)?;
+        }
+    }
+    std::fs::remove_file(journal)?;
+    Ok(())
+}
+
+/// Take an exclusive advisory lock on `file`, waiting up to `timeout` for other processes.
+/// note: the lock is released when the returned file is dropped
+#[tracing::instrument(parent = None)]
+fn lock_file(file: &std::path::Path, timeout: std::time::Duration) -> Result<std::fs::File> {
+    let lock = open_lock_file(file)?;
+    let start = std::time::Instant::now();
+    loop {
+        match lock.try_lock() {
+            Ok(()) => return Ok(lock),
+            Err(std::fs::TryLockError::WouldBlock) if start.elapsed() < timeout => {
+                tracing::debug!("waiting for lock {:?}", file);
+                std::thread::sleep(LOCK_RETRY_INTERVAL);
+            }
+            Err(std::fs::TryLockError::WouldBlock) => {
+                return Err(TodoTxtRsError::LockTimeout(file.to_path_buf()))
+            }
+            Err(std::fs::TryLockError::Error(e)) => return Err(e.into()),
+        }
+    }
+}
+
+fn open_lock_file(file: &std::path::Path) -> Result<std::fs::File> {
+    Ok(std::fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(file)?)
+}
+
+#[tracing::instrument(parent = None, skip(lines))]
+fn write_lines_to_temp(
+    tmp: &std::path::Path,
+    file: &std::path::Path,
+    lines: &[String],
+) -> Result<()> {
+    use std::io::Write;
+    let out = std::fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(tmp)?;
+    if let Ok(metadata) = std::fs::metadata(file) {
+        out.set_permissions(metadata.permissions())?;
+    }
+    let mut writer = std::io::BufWriter::new(out);
+
+    for line in lines {
+        write_line(&mut writer, line)?;
+    }
+    writer.flush()?;
+    writer.get_ref().sync_all()?;
+    Ok(())
+}
+
+fn temp_file_of(file: &std::path::Path) -> std::path::PathBuf {
+    let mut name = file.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    file.with_file_name(name)
+}
+
+/// One `<temporary file>\t<target file>` line per pending rename.
+fn journal_content(renames: &[(std::path::PathBuf, std::path::PathBuf)]) -> String {
+    renames
+        .iter()
+        .map(|(tmp, file)| format!("{}\t{}\n", tmp.display(), file.display()))
+        .collect()
+}
+
+fn parse_journal(content: &str) -> Vec<(std::path::PathBuf, std::path::PathBuf)> {
+    content
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .map(|(tmp, file)| (tmp.into(), file.into()))
+        .collect()
+}
+
+/// Make a rename durable.
+/// note: directories can not be opened for syncing on windows, renames are durable there
+fn sync_parent_dir(file: &std::path::Path) -> Result<()> {
+    #[cfg(unix)]
+    if let Some(dir) = file.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::File::open(dir)?.sync_all()?;
+    }
+    #[cfg(not(unix))]
+    let _ = file;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("todo-txt-rs-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn lines(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|l| l.to_string()).collect()
+    }
+
+    #[test]
+    fn test_save_and_recover() {
+        let dir = temp_dir("recover");
+        let storage = FileStorage::new(&dir);
+        let todo = dir.join("todo.txt");
+        let done = dir.join("done.txt");
+
+        storage
+            .save(&[
+                ("todo.txt".to_string(), lines(&["a task"])),
+                ("done.txt".to_string(), Vec::new()),
+            ])
+            .unwrap();
+        assert!(!storage.needs_recovery());
+        assert_eq!(storage.load("todo.txt").unwrap(), lines(&["a task"]));
+        assert_eq!(storage.load("missing.txt").unwrap(), Vec::<String>::new());
+
+        // interrupted after the journal was written and only todo.txt was renamed
+        let renames = [
+            (temp_file_of(&todo), todo.clone()),
+            (temp_file_of(&done), done.clone()),
+        ];
+        write_lines_to_temp(&renames[0].0, &todo, &[]).unwrap();
+        write_lines_to_temp(&renames[1].0, &done, &lines(&["x a done task"])).unwrap();
+        std::fs::write(&storage.journal_file, journal_content(&renames)).unwrap();
+        std::fs::rename(&renames[0].0, &todo).unwrap();
+        assert!(storage.needs_recovery());
+
+        drop(storage.lock(std::time::Duration::from_millis(100)).unwrap());
+        assert!(!storage.needs_recovery());
+        assert_eq!(storage.load("todo.txt").unwrap(), Vec::<String>::new());
+        assert_eq!(storage.load("done.txt").unwrap(), lines(&["x a done task"]));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_lock_file() {
+        let dir = temp_dir("lock");
+        let file = dir.join(".todo.lock");
+        let timeout = std::time::Duration::from_millis(100);
+        let lock = lock_file(&file, timeout).unwrap();
+        assert!(matches!(
+            lock_file(&file, timeout),
+            Err(TodoTxtRsError::LockTimeout(_))
+        ));
+        drop(lock);
+        assert!(lock_file(&file, timeout).is_ok());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
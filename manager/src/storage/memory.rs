@@ -0,0 +1,94 @@
+use super::{Storage, StorageLock};
+use todo_txt_model::prelude::*;
+
+/// Files kept in memory, e.g. for tests or a preview that must not touch the disk.
+/// note: clones share the same files and lock
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStorage {
+    inner: std::sync::Arc<Inner>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    files: std::sync::Mutex<std::collections::HashMap<String, Vec<String>>>,
+    locked: std::sync::Mutex<bool>,
+    released: std::sync::Condvar,
+}
+
+/// Releases the lock of a `MemoryStorage` when dropped.
+struct MemoryLock(std::sync::Arc<Inner>);
+
+impl Drop for MemoryLock {
+    fn drop(&mut self) {
+        *self.0.locked.lock().unwrap_or_else(|e| e.into_inner()) = false;
+        self.0.released.notify_one();
+    }
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn load(&self, name: &str) -> Result<Vec<String>> {
+        let files = self.inner.files.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(files.get(name).cloned().unwrap_or_default())
+    }
+
+    fn save(&self, files: &[(String, Vec<String>)]) -> Result<()> {
+        let mut stored = self.inner.files.lock().unwrap_or_else(|e| e.into_inner());
+        for (name, lines) in files {
+            stored.insert(name.clone(), lines.clone());
+        }
+        Ok(())
+    }
+
+    fn names(&self) -> Result<Vec<String>> {
+        let files = self.inner.files.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(files.keys().cloned().collect())
+    }
+
+    fn lock(&self, timeout: std::time::Duration) -> Result<StorageLock> {
+        let locked = self.inner.locked.lock().unwrap_or_else(|e| e.into_inner());
+        let (mut locked, wait) = self
+            .inner
+            .released
+            .wait_timeout_while(locked, timeout, |locked| *locked)
+            .unwrap_or_else(|e| e.into_inner());
+        if wait.timed_out() {
+            return Err(TodoTxtRsError::LockTimeout(self.path("")));
+        }
+        *locked = true;
+        Ok(StorageLock::new(MemoryLock(self.inner.clone())))
+    }
+
+    fn path(&self, name: &str) -> std::path::PathBuf {
+        std::path::Path::new("memory:").join(name)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_memory_storage() {
+        let storage = MemoryStorage::new();
+        assert!(storage.load("todo.txt").unwrap().is_empty());
+        storage
+            .save(&[("todo.txt".to_string(), vec!["a task".to_string()])])
+            .unwrap();
+        assert_eq!(storage.clone().load("todo.txt").unwrap(), vec!["a task"]);
+
+        let timeout = std::time::Duration::from_millis(50);
+        let lock = storage.lock(timeout).unwrap();
+        assert!(matches!(
+            storage.lock(timeout),
+            Err(TodoTxtRsError::LockTimeout(_))
+        ));
+        drop(lock);
+        assert!(storage.lock(timeout).is_ok());
+    }
+}
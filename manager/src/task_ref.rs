@@ -0,0 +1,215 @@
+use todo_txt_model::prelude::*;
+
+/// Reference to a single task.
+///
+/// `Index` is the 1-based position printed by `list` and changes whenever tasks above it
+/// are added, deleted or archived. `Uid` is the value of the task's `id:` tag and does not.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TaskRef {
+    Index(usize),
+    Uid(String),
+}
+
+impl From<usize> for TaskRef {
+    fn from(value: usize) -> Self {
+        Self::Index(value)
+    }
+}
+
+/// Numbers are read as an index, anything else as a uid. An `id:` prefix, as printed by
+/// `Display`, makes any value a uid, e.g. `id:42`.
+impl std::str::FromStr for TaskRef {
+    type Err = TodoTxtRsError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if let Some(uid) = s.strip_prefix(ID_TAG).and_then(|s| s.strip_prefix(':')) {
+            if uid.is_empty() {
+                return Err(TodoTxtRsError::InvalidIndex);
+            }
+            return Ok(Self::Uid(uid.to_string()));
+        }
+        if s.is_empty() {
+            return Err(TodoTxtRsError::InvalidIndex);
+        }
+        Ok(s.parse::<usize>()
+            .map(Self::Index)
+            .unwrap_or_else(|_| Self::Uid(s.to_string())))
+    }
+}
+
+impl std::fmt::Display for TaskRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Index(idx) => write!(f, "{}", idx),
+            Self::Uid(uid) => write!(f, "{}:{}", ID_TAG, uid),
+        }
+    }
+}
+
+/// One or more tasks given on the command line: a single `TaskRef` or an inclusive range of
+/// indexes like `3-7`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TaskSelector {
+    Ref(TaskRef),
+    Range(usize, usize),
+}
+
+impl From<TaskRef> for TaskSelector {
+    fn from(value: TaskRef) -> Self {
+        Self::Ref(value)
+    }
+}
+
+impl From<usize> for TaskSelector {
+    fn from(value: usize) -> Self {
+        Self::Ref(TaskRef::Index(value))
+    }
+}
+
+impl From<std::ops::RangeInclusive<usize>> for TaskSelector {
+    fn from(value: std::ops::RangeInclusive<usize>) -> Self {
+        Self::Range(*value.start(), *value.end())
+    }
+}
+
+/// `a-b` with two numbers is read as a range, anything else as a `TaskRef`.
+/// note: uids may contain `-`, so `ab-cd` is a uid
+impl std::str::FromStr for TaskSelector {
+    type Err = TodoTxtRsError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if let Some((start, end)) = s.split_once('-') {
+            if let (Ok(start), Ok(end)) = (start.parse::<usize>(), end.parse::<usize>()) {
+                if start == 0 || start > end {
+                    return Err(TodoTxtRsError::InvalidIndex);
+                }
+                return Ok(Self::Range(start, end));
+            }
+        }
+        s.parse().map(Self::Ref)
+    }
+}
+
+impl std::fmt::Display for TaskSelector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Ref(task_ref) => write!(f, "{}", task_ref),
+            Self::Range(start, end) => write!(f, "{}-{}", start, end),
+        }
+    }
+}
+
+/// Generate a uid that is not used by any of `tasks`.
+/// note: it starts with a letter, so that it is never read as an index
+pub(crate) fn generate_uid<'a>(tasks: impl Iterator<Item = &'a Task> + Clone) -> String {
+    use std::hash::BuildHasher;
+    const LEN: usize = 8;
+    const LETTERS: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+    const DIGITS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+    let state = std::hash::RandomState::new();
+    let mut seed = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    loop {
+        let mut hash = state.hash_one(seed);
+        let uid = (0..LEN)
+            .map(|i| {
+                let chars = if i == 0 { LETTERS } else { DIGITS };
+                let c = chars[(hash % chars.len() as u64) as usize] as char;
+                hash /= chars.len() as u64;
+                c
+            })
+            .collect::<String>();
+        if !tasks.clone().any(|t| t.uid() == Some(uid.as_str())) {
+            return uid;
+        }
+        seed += 1;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_task_selector() {
+        assert_eq!("3".parse::<TaskSelector>().unwrap(), TaskSelector::from(3));
+        assert_eq!(
+            "8-10".parse::<TaskSelector>().unwrap(),
+            TaskSelector::Range(8, 10)
+        );
+        assert_eq!(
+            "ab-cd".parse::<TaskSelector>().unwrap(),
+            TaskSelector::Ref(TaskRef::Uid("ab-cd".to_string()))
+        );
+        assert!("10-8".parse::<TaskSelector>().is_err());
+        assert!("0-2".parse::<TaskSelector>().is_err());
+        assert_eq!(TaskSelector::Range(3, 7).to_string(), "3-7");
+        assert_eq!(
+            "id:1-2".parse::<TaskSelector>().unwrap(),
+            TaskSelector::Ref(TaskRef::Uid("1-2".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_task_ref() {
+        assert_eq!("3".parse::<TaskRef>().unwrap(), TaskRef::Index(3));
+        assert_eq!(
+            "abc".parse::<TaskRef>().unwrap(),
+            TaskRef::Uid("abc".to_string())
+        );
+        assert_eq!(
+            "id:42".parse::<TaskRef>().unwrap(),
+            TaskRef::Uid("42".to_string())
+        );
+        for task_ref in [TaskRef::Index(7), TaskRef::Uid("42".to_string())] {
+            assert_eq!(task_ref.to_string().parse::<TaskRef>().unwrap(), task_ref);
+        }
+        assert!("".parse::<TaskRef>().is_err());
+        assert!("id:".parse::<TaskRef>().is_err());
+    }
+
+    #[test]
+    fn test_generate_uid() {
+        let tasks = ["a id:x1", "b"]
+            .map(|line| todo_txt_serializer::from_str(line).unwrap())
+            .to_vec();
+        for _ in 0..100 {
+            let uid = generate_uid(tasks.iter());
+            assert_eq!(uid.len(), 8);
+            assert!(uid.starts_with(|c: char| c.is_ascii_lowercase()));
+            assert!(matches!(uid.parse::<TaskRef>(), Ok(TaskRef::Uid(_))));
+            assert_ne!(uid, "x1");
+        }
+    }
+
+    #[test]
+    fn test_get_by_uid() {
+        use crate::{MemoryStorage, Storage};
+        let storage = MemoryStorage::new();
+        let manager = crate::TodoManagerBuilder::new()
+            .with_env(false)
+            .build()
+            .unwrap()
+            .with_storage(storage.clone());
+        storage
+            .save(&[(
+                "todo.txt".to_string(),
+                vec!["a id:42".to_string(), "b id:abc".to_string()],
+            )])
+            .unwrap();
+        let get = |s: &str| {
+            manager
+                .get(s.parse::<TaskRef>().unwrap())
+                .map(|(idx, _)| idx)
+        };
+        assert_eq!(get("id:42").unwrap(), 1);
+        assert_eq!(get("abc").unwrap(), 2);
+        assert_eq!(get("id:abc").unwrap(), 2);
+        assert_eq!(get("2").unwrap(), 2);
+        assert!(matches!(get("id:43"), Err(TodoTxtRsError::NotFound)));
+    }
+}
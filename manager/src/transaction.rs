@@ -0,0 +1,286 @@
+use crate::selection::operation_label;
+use crate::{Selection, TaskRef, TodoManager};
+use todo_txt_model::prelude::*;
+
+/// Tasks loaded once by `TodoManager::transaction` and edited in memory.
+///
+/// Indexes refer to the current state of the transaction, so they shift after a `delete`
+/// like they do between two commands.
+#[derive(Debug)]
+pub struct Transaction<'a> {
+    manager: &'a TodoManager,
+    tasks: Vec<(usize, Task)>,
+    operations: Vec<String>,
+}
+
+impl<'a> Transaction<'a> {
+    fn new(manager: &'a TodoManager, tasks: Vec<(usize, Task)>) -> Self {
+        Self {
+            manager,
+            tasks,
+            operations: Vec::new(),
+        }
+    }
+
+    /// Every task with its 1-based index, todo.txt first.
+    pub fn tasks(&self) -> impl Iterator<Item = (usize, &Task)> {
+        self.tasks
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, t))| !crate::commands::is_placeholder(t))
+            .map(|(pos, (_, t))| (pos + 1, t))
+    }
+
+    pub fn get(&self, task_ref: impl Into<TaskRef>) -> Result<(usize, &Task)> {
+        let pos = self.position(task_ref.into())?;
+        Ok((pos + 1, &self.tasks[pos].1))
+    }
+
+    /// Like `TodoManager::add`, returning the index of the new task.
+    /// note: like `add`, a new done task is written to done.txt only with `ArchivePolicy::Auto`
+    pub fn add(&mut self, mut new_task: Task) -> Result<(usize, Task)> {
+        crate::commands::add::fill_dates(&mut new_task, self.manager.date_on_add);
+        if self.manager.auto_id && new_task.uid().is_none() {
+            let uid = crate::task_ref::generate_uid(self.tasks.iter().map(|(_, t)| t));
+            new_task.set_uid(Some(uid));
+        }
+        self.operations
+            .push(format!("add {}", todo_txt_serializer::to_string(&new_task)));
+        // above every index read, which marks it as added for `save_tasks` even after a delete
+        let id = self.tasks.iter().map(|(id, _)| *id).max().unwrap_or(0) + 1;
+        self.tasks.push((id, new_task.clone()));
+        Ok((self.tasks.len(), new_task))
+    }
+
+    pub fn update_state(
+        &mut self,
+        selection: impl Into<Selection>,
+        done: bool,
+    ) -> Result<Vec<(usize, Task)>> {
+        let updated =
+            self.manager
+                .update_state_selected(&selection.into(), done, &mut self.tasks)?;
+        self.push_operation(if done { "done" } else { "undone" }, &updated);
+        Ok(updated)
+    }
+
+    pub fn set_priority(
+        &mut self,
+        selection: impl Into<Selection>,
+        priority: Option<TaskPriority>,
+    ) -> Result<Vec<(usize, Task)>> {
+        let updated = crate::commands::priority::set_priority_selected(
+            &selection.into(),
+            priority,
+            &mut self.tasks,
+        )?;
+        self.push_operation("priority", &updated);
+        Ok(updated)
+    }
+
+    pub fn append(
+        &mut self,
+        selection: impl Into<Selection>,
+        description: TaskDescription,
+    ) -> Result<Vec<(usize, Task)>> {
+        let updated = crate::commands::append::append_selected(
+            &selection.into(),
+            &description,
+            &mut self.tasks,
+        )?;
+        self.push_operation("append", &updated);
+        Ok(updated)
+    }
+
+    pub fn delete(&mut self, selection: impl Into<Selection>) -> Result<Vec<(usize, Task)>> {
+        let deleted = crate::commands::delete::delete_selected(
+            &selection.into(),
+            self.manager.preserve_line_numbers,
+            &mut self.tasks,
+        )?;
+        self.push_operation("delete", &deleted);
+        Ok(deleted)
+    }
+
+    /// Like `TodoManager::replace`, returning the index, the old task and the new one.
+    pub fn replace(
+        &mut self,
+        task_ref: impl Into<TaskRef>,
+        mut new_task: Task,
+    ) -> Result<(usize, Task, Task)> {
+        let task_ref = task_ref.into();
+        let pos = self.position(task_ref.clone())?;
+        crate::commands::add::fill_dates(&mut new_task, self.manager.date_on_add);
+        let old = &mut self.tasks[pos].1;
+        if new_task.uid().is_none() {
+            new_task.set_uid(old.uid().map(str::to_string));
+        }
+        let old = std::mem::replace(old, new_task.clone());
+        self.operations.push(format!("replace {}", task_ref));
+        Ok((pos + 1, old, new_task))
+    }
+
+    fn position(&self, task_ref: TaskRef) -> Result<usize> {
+        Selection::from(task_ref)
+            .positions(&self.tasks, chrono::Utc::now().date_naive())?
+            .pop()
+            .ok_or(TodoTxtRsError::NotFound)
+    }
+
+    fn push_operation(&mut self, operation: &str, changed: &[(usize, Task)]) {
+        if !changed.is_empty() {
+            self.operations.push(operation_label(operation, changed));
+        }
+    }
+
+    /// Label of the whole transaction for the history, e.g. `done 2 3; priority 1`.
+    fn operation(&self) -> String {
+        self.operations.join("; ")
+    }
+}
+
+impl TodoManager {
+    /// Load the tasks once, let `edit` change them in memory, and write every change in a
+    /// single commit when it returns `Ok`.
+    /// note: nothing is written when `edit` fails, and the lock is held until the end
+    /// note: the transaction is undone as a whole
+    #[tracing::instrument(parent = None, skip(self, edit))]
+    pub fn transaction<T>(&self, edit: impl FnOnce(&mut Transaction) -> Result<T>) -> Result<T> {
+        let _lock = self.lock()?;
+        let (tasks, layout) = self.read_for_write()?;
+        let mut transaction = Transaction::new(self, tasks);
+        let value = edit(&mut transaction)?;
+        if !transaction.operations.is_empty() {
+            let operation = transaction.operation();
+            tracing::debug!("commit: {}", operation);
+            self.save_tasks(&operation, transaction.tasks, &layout)?;
+        }
+        Ok(value)
+    }
+
+    /// note: `edit` runs on the blocking thread pool like the rest of the transaction
+    #[cfg(feature = "async")]
+    pub async fn transaction_async<T: Send + 'static>(
+        &self,
+        edit: impl FnOnce(&mut Transaction) -> Result<T> + Send + 'static,
+    ) -> Result<T> {
+        self.unblock(move |manager| manager.transaction(edit)).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{MemoryStorage, Storage};
+
+    fn task(line: &str) -> Task {
+        todo_txt_serializer::from_str(line).unwrap()
+    }
+
+    fn lines(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|l| l.to_string()).collect()
+    }
+
+    #[test]
+    fn test_transaction_commit_and_rollback() {
+        let storage = MemoryStorage::new();
+        let manager = crate::TodoManagerBuilder::new()
+            .with_env(false)
+            .build()
+            .unwrap()
+            .with_storage(storage.clone());
+        storage
+            .save(&[("todo.txt".to_string(), lines(&["a", "b", "c"]))])
+            .unwrap();
+
+        let result = manager.transaction(|tx| {
+            tx.update_state(2, true)?;
+            tx.set_priority(1, Some(TaskPriority::B))?;
+            tx.delete(2)?;
+            tx.update_state(9, true)
+        });
+        assert!(matches!(result, Err(TodoTxtRsError::InvalidIndex)));
+        assert_eq!(storage.load("todo.txt").unwrap(), lines(&["a", "b", "c"]));
+
+        let added = manager
+            .transaction(|tx| {
+                tx.update_state(2, true)?;
+                tx.set_priority(1, Some(TaskPriority::B))?;
+                tx.delete(3)?;
+                tx.add(task("2021-01-01 d"))
+            })
+            .unwrap();
+        assert_eq!(added.0, 3);
+        assert_eq!(
+            storage.load("todo.txt").unwrap(),
+            lines(&["(B) a", "2021-01-01 d"])
+        );
+        assert_eq!(storage.load("done.txt").unwrap().len(), 1);
+        assert_eq!(
+            manager.undo().unwrap(),
+            "done 2; priority 1; delete 3; add 2021-01-01 d"
+        );
+        assert_eq!(storage.load("todo.txt").unwrap(), lines(&["a", "b", "c"]));
+    }
+
+    #[test]
+    fn test_transaction_add_done_with_manual_archive() {
+        let storage = MemoryStorage::new();
+        let manager = crate::TodoManagerBuilder::new()
+            .with_env(false)
+            .build()
+            .unwrap()
+            .with_storage(storage.clone())
+            .with_archive_policy(crate::ArchivePolicy::Manual);
+        storage
+            .save(&[("todo.txt".to_string(), lines(&["a", "b", "c"]))])
+            .unwrap();
+
+        manager
+            .transaction(|tx| {
+                tx.delete(1)?;
+                tx.add(task("x 2021-01-02 2021-01-01 d"))
+            })
+            .unwrap();
+        manager.add(task("x 2021-01-02 2021-01-01 e")).unwrap();
+        assert_eq!(
+            storage.load("todo.txt").unwrap(),
+            lines(&[
+                "b",
+                "c",
+                "x 2021-01-02 2021-01-01 d",
+                "x 2021-01-02 2021-01-01 e"
+            ])
+        );
+        assert!(storage.load("done.txt").unwrap().is_empty());
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_transaction_async_without_runtime() {
+        let storage = MemoryStorage::new();
+        let manager = crate::TodoManagerBuilder::new()
+            .with_env(false)
+            .build()
+            .unwrap()
+            .with_storage(storage.clone());
+        storage
+            .save(&[("todo.txt".to_string(), lines(&["a", "b"]))])
+            .unwrap();
+
+        // note: no tokio, async-std or smol runtime is running here
+        let updated = futures_lite::future::block_on(async {
+            manager.update_state_async(1, true).await?;
+            manager
+                .transaction_async(|tx| tx.set_priority(1, Some(TaskPriority::A)))
+                .await
+        })
+        .unwrap();
+        assert_eq!(updated.len(), 1);
+        assert_eq!(storage.load("todo.txt").unwrap(), lines(&["(A) b"]));
+        assert_eq!(
+            futures_lite::future::block_on(manager.undo_async()).unwrap(),
+            "priority 1"
+        );
+    }
+}
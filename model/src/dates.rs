@@ -0,0 +1,122 @@
+use crate::{Task, TaskState};
+
+pub const DATE_FORMAT: &str = "%Y-%m-%d";
+pub const DUE_TAG: &str = "due";
+pub const THRESHOLD_TAG: &str = "t";
+/// Number of days ahead of the due date in which a task is considered due soon.
+pub const DUE_SOON_DAYS: i64 = 3;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord, clap::ValueEnum)]
+#[clap(rename_all = "snake_case")]
+pub enum TaskUrgency {
+    Overdue,
+    DueToday,
+    DueSoon,
+    Normal,
+    NotYetVisible,
+}
+
+impl Task {
+    /// Date of the `due:` tag.
+    /// note: returns None when the tag is missing or is not a valid date
+    pub fn due_date(&self) -> Option<chrono::NaiveDate> {
+        self.date_tag(DUE_TAG)
+    }
+
+    pub fn set_due_date(&mut self, date: Option<chrono::NaiveDate>) {
+        self.set_date_tag(DUE_TAG, date);
+    }
+
+    /// Date of the `t:` tag. The task should not be shown before this date.
+    /// note: returns None when the tag is missing or is not a valid date
+    pub fn threshold_date(&self) -> Option<chrono::NaiveDate> {
+        self.date_tag(THRESHOLD_TAG)
+    }
+
+    pub fn set_threshold_date(&mut self, date: Option<chrono::NaiveDate>) {
+        self.set_date_tag(THRESHOLD_TAG, date);
+    }
+
+    /// Whether the threshold date has been reached.
+    pub fn is_visible(&self, today: chrono::NaiveDate) -> bool {
+        self.threshold_date().is_none_or(|t| t <= today)
+    }
+
+    /// Urgency of the task as of `today`.
+    /// note: done tasks are always Normal
+    /// note: a task whose threshold date is in the future is NotYetVisible even if it is due
+    pub fn urgency(&self, today: chrono::NaiveDate) -> TaskUrgency {
+        if self.state == TaskState::Done {
+            return TaskUrgency::Normal;
+        }
+        if !self.is_visible(today) {
+            return TaskUrgency::NotYetVisible;
+        }
+        match self.due_date() {
+            Some(due) if due < today => TaskUrgency::Overdue,
+            Some(due) if due == today => TaskUrgency::DueToday,
+            Some(due) if (due - today).num_days() <= DUE_SOON_DAYS => TaskUrgency::DueSoon,
+            _ => TaskUrgency::Normal,
+        }
+    }
+
+    fn date_tag(&self, key: &str) -> Option<chrono::NaiveDate> {
+        self.description
+            .tags
+            .get(key)
+            .and_then(|v| chrono::NaiveDate::parse_from_str(v, DATE_FORMAT).ok())
+    }
+
+    fn set_date_tag(&mut self, key: &str, date: Option<chrono::NaiveDate>) {
+        if let Some(date) = date {
+            self.description
+                .tags
+                .insert(key, date.format(DATE_FORMAT).to_string());
+        } else {
+            self.description.tags.remove(key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> chrono::NaiveDate {
+        chrono::NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_due_and_threshold_date() {
+        let mut task = Task::default();
+        assert_eq!(task.due_date(), None);
+        task.description.tags.insert(DUE_TAG, "not-a-date");
+        assert_eq!(task.due_date(), None);
+        task.set_due_date(Some(date(2021, 1, 10)));
+        assert_eq!(task.due_date(), Some(date(2021, 1, 10)));
+        assert_eq!(task.description.tags.get(DUE_TAG), Some("2021-01-10"));
+        task.set_threshold_date(Some(date(2021, 1, 5)));
+        assert_eq!(task.threshold_date(), Some(date(2021, 1, 5)));
+        task.set_due_date(None);
+        assert!(!task.description.tags.contains_key(DUE_TAG));
+    }
+
+    #[test]
+    fn test_urgency() {
+        let today = date(2021, 1, 10);
+        let mut task = Task::default();
+        assert_eq!(task.urgency(today), TaskUrgency::Normal);
+        task.set_due_date(Some(date(2021, 1, 9)));
+        assert_eq!(task.urgency(today), TaskUrgency::Overdue);
+        task.set_due_date(Some(today));
+        assert_eq!(task.urgency(today), TaskUrgency::DueToday);
+        task.set_due_date(Some(date(2021, 1, 13)));
+        assert_eq!(task.urgency(today), TaskUrgency::DueSoon);
+        task.set_due_date(Some(date(2021, 1, 14)));
+        assert_eq!(task.urgency(today), TaskUrgency::Normal);
+        task.set_threshold_date(Some(date(2021, 1, 11)));
+        assert_eq!(task.urgency(today), TaskUrgency::NotYetVisible);
+        task.state = TaskState::Done;
+        assert_eq!(task.urgency(today), TaskUrgency::Normal);
+    }
+}
@@ -2,13 +2,109 @@ pub type Result<T> = std::result::Result<T, Error>;
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
-    #[error("exception invalid syntax")]
-    Syntax,
+    #[error("exception invalid syntax: {0}")]
+    Syntax(SyntaxError),
     #[error("exception invalid index")]
     InvalidIndex,
+    #[error("exception task not found")]
+    NotFound,
+    #[error("exception invalid priority: {0}")]
+    InvalidPriority(String),
+    #[error("exception invalid sort key: {0}")]
+    InvalidSortKey(String),
+    #[error("exception invalid recurrence")]
+    InvalidRecurrence,
+    #[error("exception lock timeout: {}", .0.display())]
+    LockTimeout(std::path::PathBuf),
+    #[error("exception invalid query: {0}")]
+    InvalidQuery(String),
+    #[error("exception invalid pattern: {0}")]
+    InvalidPattern(String),
+    #[error("exception nothing to undo")]
+    NothingToUndo,
+    #[error("exception nothing to redo")]
+    NothingToRedo,
+    #[error("exception {0} was changed since the operation")]
+    HistoryConflict(String),
+    #[error("exception invalid path: {}: {}", .0.display(), .1)]
+    InvalidPath(std::path::PathBuf, String),
+    #[error("exception invalid config: {0}")]
+    InvalidConfig(String),
+    #[error("exception invalid report line: {0}")]
+    InvalidReport(String),
+    #[error("exception invalid list name: {0}")]
+    InvalidList(String),
 
     #[error(transparent)]
     Io(#[from] std::io::Error),
     #[error(transparent)]
     Convert(#[from] core::convert::Infallible),
 }
+
+impl From<SyntaxError> for Error {
+    fn from(value: SyntaxError) -> Self {
+        Self::Syntax(value)
+    }
+}
+
+impl Error {
+    /// Attach the 1-based source line number to a syntax error.
+    /// note: other errors are returned as is
+    pub fn with_line(self, line: usize) -> Self {
+        match self {
+            Self::Syntax(e) => Self::Syntax(SyntaxError {
+                line: Some(line),
+                ..e
+            }),
+            e => e,
+        }
+    }
+}
+
+/// Why a line could not be parsed as a task.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SyntaxReason {
+    EmptyLine,
+    EmptyDescription,
+    /// A completion marker, priority or date found in the description.
+    UnexpectedToken,
+}
+
+impl std::fmt::Display for SyntaxReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::EmptyLine => write!(f, "empty line"),
+            Self::EmptyDescription => write!(f, "empty description"),
+            Self::UnexpectedToken => write!(f, "unexpected token"),
+        }
+    }
+}
+
+/// Location and reason of a syntax error.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SyntaxError {
+    /// 1-based line number, when parsed from a file
+    pub line: Option<usize>,
+    /// Byte range of the offending token in the parsed line
+    pub span: std::ops::Range<usize>,
+    pub reason: SyntaxReason,
+}
+
+impl SyntaxError {
+    pub fn new(span: std::ops::Range<usize>, reason: SyntaxReason) -> Self {
+        Self {
+            line: None,
+            span,
+            reason,
+        }
+    }
+}
+
+impl std::fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "line {}, ", line)?;
+        }
+        write!(f, "column {}: {}", self.span.start + 1, self.reason)
+    }
+}
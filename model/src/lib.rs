@@ -1,4 +1,12 @@
+mod dates;
 mod error;
+mod recurrence;
+mod tags;
+
+pub use dates::*;
+pub use error::{SyntaxError, SyntaxReason};
+pub use recurrence::*;
+pub use tags::TaskTags;
 
 pub mod prelude {
     pub use crate::error::{Error as TodoTxtRsError, Result};
@@ -14,10 +22,25 @@ pub struct Task {
     pub description: TaskDescription,
 }
 
+pub const ID_TAG: &str = "id";
+
 impl Task {
     pub fn is_done(&self) -> bool {
         self.state == TaskState::Done
     }
+
+    /// Stable identifier of the `id:` tag.
+    pub fn uid(&self) -> Option<&str> {
+        self.description.tags.get(ID_TAG)
+    }
+
+    pub fn set_uid(&mut self, uid: Option<String>) {
+        if let Some(uid) = uid {
+            self.description.tags.insert(ID_TAG, uid);
+        } else {
+            self.description.tags.remove(ID_TAG);
+        }
+    }
 }
 
 #[derive(Debug, Default, PartialEq, Eq, Clone, Copy, PartialOrd, Ord, clap::ValueEnum)]
@@ -161,9 +184,34 @@ impl From<i32> for TaskPriority {
     }
 }
 
-#[derive(Debug, Default, PartialEq, Eq, Clone)]
+#[derive(Debug, Default, Clone)]
 pub struct TaskDescription {
     pub value: String,
     pub project: Vec<String>,
     pub context: Vec<String>,
+    pub tags: TaskTags,
+    /// Tokens in the order they were written.
+    /// note: only the layout is kept here, the fields above hold the content
+    /// note: empty for descriptions that were not parsed from a string
+    pub tokens: Vec<DescriptionToken>,
+}
+
+/// Two descriptions are equal when their content is equal, regardless of the token order.
+impl PartialEq for TaskDescription {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+            && self.project == other.project
+            && self.context == other.context
+            && self.tags == other.tags
+    }
+}
+
+impl Eq for TaskDescription {}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum DescriptionToken {
+    Word(String),
+    Project(String),
+    Context(String),
+    Tag(String, String),
 }
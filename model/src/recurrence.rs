@@ -0,0 +1,249 @@
+use crate::{error::Error, Task, TaskState};
+
+pub const RECURRENCE_TAG: &str = "rec";
+pub const STRICT_RECURRENCE_MARKER: char = '+';
+/// Largest amount of a `rec:` tag, e.g. `9999d`, larger ones are invalid.
+pub const MAX_RECURRENCE_AMOUNT: u32 = 9999;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RecurrenceUnit {
+    Day,
+    BusinessDay,
+    Week,
+    Month,
+    Year,
+}
+
+impl From<RecurrenceUnit> for char {
+    fn from(value: RecurrenceUnit) -> Self {
+        match value {
+            RecurrenceUnit::Day => 'd',
+            RecurrenceUnit::BusinessDay => 'b',
+            RecurrenceUnit::Week => 'w',
+            RecurrenceUnit::Month => 'm',
+            RecurrenceUnit::Year => 'y',
+        }
+    }
+}
+
+impl TryFrom<char> for RecurrenceUnit {
+    type Error = Error;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        match value.to_ascii_lowercase() {
+            'd' => Ok(RecurrenceUnit::Day),
+            'b' => Ok(RecurrenceUnit::BusinessDay),
+            'w' => Ok(RecurrenceUnit::Week),
+            'm' => Ok(RecurrenceUnit::Month),
+            'y' => Ok(RecurrenceUnit::Year),
+            _ => Err(Error::InvalidRecurrence),
+        }
+    }
+}
+
+/// Value of a `rec:` tag, e.g. `1w`, `+1m` or `2b`.
+///
+/// A strict recurrence (`+`) is based on the previous due date, otherwise the next
+/// occurrence is based on the completion date.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Recurrence {
+    pub strict: bool,
+    pub amount: u32,
+    pub unit: RecurrenceUnit,
+}
+
+impl std::str::FromStr for Recurrence {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (strict, s) = match s.strip_prefix(STRICT_RECURRENCE_MARKER) {
+            Some(s) => (true, s),
+            None => (false, s),
+        };
+        let unit = s.chars().last().ok_or(Error::InvalidRecurrence)?;
+        let amount = &s[..s.len() - unit.len_utf8()];
+        let amount = if amount.is_empty() {
+            1
+        } else {
+            amount.parse().map_err(|_| Error::InvalidRecurrence)?
+        };
+        if amount == 0 || amount > MAX_RECURRENCE_AMOUNT {
+            return Err(Error::InvalidRecurrence);
+        }
+        Ok(Self {
+            strict,
+            amount,
+            unit: RecurrenceUnit::try_from(unit)?,
+        })
+    }
+}
+
+impl std::fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.strict {
+            write!(f, "{}", STRICT_RECURRENCE_MARKER)?;
+        }
+        write!(f, "{}{}", self.amount, char::from(self.unit))
+    }
+}
+
+impl Recurrence {
+    /// Shift `date` by one recurrence interval, `None` when the result is out of range.
+    /// note: months and years are clamped to the end of the month, e.g. 01-31 + 1m is 02-28
+    /// note: business days skip saturdays and sundays
+    pub fn next(&self, date: chrono::NaiveDate) -> Option<chrono::NaiveDate> {
+        use chrono::Datelike;
+        let amount = u64::from(self.amount);
+        match self.unit {
+            RecurrenceUnit::Day => date.checked_add_days(chrono::Days::new(amount)),
+            RecurrenceUnit::Week => {
+                date.checked_add_days(chrono::Days::new(amount.checked_mul(7)?))
+            }
+            RecurrenceUnit::Month => date.checked_add_months(chrono::Months::new(self.amount)),
+            RecurrenceUnit::Year => {
+                date.checked_add_months(chrono::Months::new(self.amount.checked_mul(12)?))
+            }
+            RecurrenceUnit::BusinessDay => {
+                // every 5 business days are a whole week, whatever the day of `date`
+                let mut date = date.checked_add_days(chrono::Days::new((amount - 1) / 5 * 7))?;
+                let mut left = (amount - 1) % 5 + 1;
+                while left > 0 {
+                    date = date.succ_opt()?;
+                    if !matches!(date.weekday(), chrono::Weekday::Sat | chrono::Weekday::Sun) {
+                        left -= 1;
+                    }
+                }
+                Some(date)
+            }
+        }
+    }
+}
+
+impl Task {
+    /// Recurrence of the `rec:` tag.
+    /// note: returns None when the tag is missing or invalid
+    pub fn recurrence(&self) -> Option<Recurrence> {
+        self.description
+            .tags
+            .get(RECURRENCE_TAG)
+            .and_then(|v| v.parse().ok())
+    }
+
+    /// Build the next occurrence of a recurring task completed on `completed`.
+    /// note: due and threshold dates are shifted, keeping the distance between them
+    /// note: a recurring task without due and threshold dates gets a due date
+    /// note: returns None when the next dates are out of range
+    pub fn next_occurrence(&self, completed: chrono::NaiveDate) -> Option<Task> {
+        let rec = self.recurrence()?;
+        let mut next = self.clone();
+        next.state = TaskState::Todo;
+        next.completed_date = None;
+        next.created_date = Some(completed);
+
+        let due = self.due_date();
+        let threshold = self.threshold_date();
+        let base = |date: Option<chrono::NaiveDate>| match date {
+            Some(date) if rec.strict => date,
+            _ => completed,
+        };
+        match (due, threshold) {
+            (Some(due), threshold) => {
+                let next_due = rec.next(base(Some(due)))?;
+                next.set_due_date(Some(next_due));
+                if let Some(threshold) = threshold {
+                    next.set_threshold_date(Some(next_due.checked_sub_signed(due - threshold)?));
+                }
+            }
+            (None, Some(threshold)) => {
+                next.set_threshold_date(Some(rec.next(base(Some(threshold)))?));
+            }
+            (None, None) => {
+                next.set_due_date(Some(rec.next(completed)?));
+            }
+        }
+        Some(next)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> chrono::NaiveDate {
+        chrono::NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_parse_recurrence() {
+        let rec = "1w".parse::<Recurrence>().unwrap();
+        assert_eq!(
+            rec,
+            Recurrence {
+                strict: false,
+                amount: 1,
+                unit: RecurrenceUnit::Week
+            }
+        );
+        let rec = "+2b".parse::<Recurrence>().unwrap();
+        assert!(rec.strict);
+        assert_eq!(rec.amount, 2);
+        assert_eq!(rec.unit, RecurrenceUnit::BusinessDay);
+        assert_eq!(rec.to_string(), "+2b");
+        assert_eq!("m".parse::<Recurrence>().unwrap().amount, 1);
+        assert!("0d".parse::<Recurrence>().is_err());
+        assert!("3x".parse::<Recurrence>().is_err());
+        assert!("99999999d".parse::<Recurrence>().is_err());
+        assert!("400000000y".parse::<Recurrence>().is_err());
+        assert!("".parse::<Recurrence>().is_err());
+    }
+
+    #[test]
+    fn test_next_date() {
+        let rec = |s: &str| s.parse::<Recurrence>().unwrap();
+        assert_eq!(rec("3d").next(date(2021, 1, 30)), Some(date(2021, 2, 2)));
+        assert_eq!(rec("1w").next(date(2021, 1, 1)), Some(date(2021, 1, 8)));
+        assert_eq!(rec("1m").next(date(2021, 1, 31)), Some(date(2021, 2, 28)));
+        assert_eq!(rec("1y").next(date(2020, 2, 29)), Some(date(2021, 2, 28)));
+        // 2021-01-08 is a friday
+        assert_eq!(rec("1b").next(date(2021, 1, 8)), Some(date(2021, 1, 11)));
+        assert_eq!(rec("6b").next(date(2021, 1, 8)), Some(date(2021, 1, 18)));
+        // 2021-01-09 is a saturday
+        assert_eq!(rec("5b").next(date(2021, 1, 9)), Some(date(2021, 1, 15)));
+        assert_eq!(rec("10b").next(date(2021, 1, 6)), Some(date(2021, 1, 20)));
+
+        let max = chrono::NaiveDate::MAX;
+        for unit in ["d", "b", "w", "m", "y"] {
+            assert_eq!(rec(&format!("9999{}", unit)).next(max), None);
+        }
+    }
+
+    #[test]
+    fn test_next_occurrence() {
+        let completed = date(2021, 1, 10);
+        let mut task = Task::default();
+        assert!(task.next_occurrence(completed).is_none());
+
+        task.description.tags.insert(RECURRENCE_TAG, "1w");
+        task.set_due_date(Some(date(2021, 1, 5)));
+        task.set_threshold_date(Some(date(2021, 1, 3)));
+        task.state = TaskState::Done;
+        task.completed_date = Some(completed);
+        let next = task.next_occurrence(completed).unwrap();
+        assert_eq!(next.state, TaskState::Todo);
+        assert_eq!(next.completed_date, None);
+        assert_eq!(next.created_date, Some(completed));
+        assert_eq!(next.due_date(), Some(date(2021, 1, 17)));
+        assert_eq!(next.threshold_date(), Some(date(2021, 1, 15)));
+
+        task.description.tags.insert(RECURRENCE_TAG, "+1w");
+        let next = task.next_occurrence(completed).unwrap();
+        assert_eq!(next.due_date(), Some(date(2021, 1, 12)));
+        assert_eq!(next.threshold_date(), Some(date(2021, 1, 10)));
+
+        let mut task = Task::default();
+        task.description.tags.insert(RECURRENCE_TAG, "2d");
+        let next = task.next_occurrence(completed).unwrap();
+        assert_eq!(next.due_date(), Some(date(2021, 1, 12)));
+        assert!(task.next_occurrence(chrono::NaiveDate::MAX).is_none());
+    }
+}
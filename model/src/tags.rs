@@ -0,0 +1,83 @@
+/// `key:value` metadata attached to a task description.
+///
+/// Tags keep the order in which they were inserted, so a parsed task writes its
+/// metadata back in the same order it was read.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct TaskTags(Vec<(String, String)>);
+
+impl TaskTags {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.0.iter().any(|(k, _)| k == key)
+    }
+
+    /// Insert a tag.
+    /// note: an existing key keeps its position and its old value is returned, a task holds
+    /// one value per key and the parser keeps the first one, reading a repeated key as text
+    pub fn insert(&mut self, key: impl Into<String>, value: impl Into<String>) -> Option<String> {
+        let key = key.into();
+        let value = value.into();
+        if let Some((_, v)) = self.0.iter_mut().find(|(k, _)| *k == key) {
+            Some(std::mem::replace(v, value))
+        } else {
+            self.0.push((key, value));
+            None
+        }
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        let idx = self.0.iter().position(|(k, _)| k == key)?;
+        Some(self.0.remove(idx).1)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(|(k, _)| k.as_str())
+    }
+}
+
+impl<K: Into<String>, V: Into<String>> Extend<(K, V)> for TaskTags {
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+impl<K: Into<String>, V: Into<String>> FromIterator<(K, V)> for TaskTags {
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut tags = Self::new();
+        tags.extend(iter);
+        tags
+    }
+}
+
+impl IntoIterator for TaskTags {
+    type Item = (String, String);
+    type IntoIter = std::vec::IntoIter<(String, String)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
@@ -7,7 +7,7 @@ use todo_txt_model::prelude::*;
 /// # Examples
 ///
 /// ```
-/// let task = todo_txt_serializer::from_str("x (A) 2020-01-01 2020-01-02 123 +project +project2 @context @ctx2 abc description");
+/// let task = todo_txt_serializer::from_str("x (A) 2020-01-01 2020-01-02 123 +project +project2 @context @ctx2 abc description due:2020-01-10");
 /// let expected = todo_txt_model::Task {
 ///                    state: todo_txt_model::TaskState::Done,
 ///                    priority: Some(todo_txt_model::TaskPriority::A),
@@ -17,23 +17,31 @@ use todo_txt_model::prelude::*;
 ///                        value: "123 abc description".to_string(),
 ///                        project: vec!["project".to_string(), "project2".to_string()],
 ///                        context: vec!["context".to_string(), "ctx2".to_string()],
+///                        tags: [("due", "2020-01-10")].into_iter().collect(),
+///                        tokens: Vec::new(),
 ///                    }
 ///                };
 /// assert_eq!(task.unwrap(), expected);
 /// ```
 #[tracing::instrument(parent = None)]
 pub fn from_str(value: &str) -> Result<todo_txt_model::Task> {
+    let offset = value.len() - value.trim_start().len();
     let value = value.trim();
     if value.is_empty() {
-        return Err(TodoTxtRsError::Syntax);
+        return Err(syntax_error(offset..offset, SyntaxReason::EmptyLine));
     }
+    let end = offset + value.len();
 
     let tokens = value
         .split(crate::TOKEN_SEPARATOR)
-        .map(Token::from)
+        .scan(offset, |pos, t| {
+            let span = *pos..*pos + t.len();
+            *pos = span.end + crate::TOKEN_SEPARATOR.len_utf8();
+            Some((span, Token::from(t)))
+        })
         .peekable();
 
-    let (state, tokens) = get_task_state(tokens)?;
+    let (state, tokens) = get_task_state(tokens);
     tracing::debug!("state: {:?}", state);
     let (priority, tokens) = get_task_priority(tokens)?;
     tracing::debug!("priority: {:?}", priority);
@@ -48,15 +56,37 @@ pub fn from_str(value: &str) -> Result<todo_txt_model::Task> {
     let mut description = String::new();
     let mut projects = Vec::new();
     let mut contexts = Vec::new();
-    for token in tokens {
+    let mut tags = todo_txt_model::TaskTags::new();
+    let mut layout = Vec::new();
+    for (span, token) in tokens {
         match token {
             Token::Project(p) => {
                 tracing::debug!("token project: {}, projects: {:?}", p, projects);
                 projects.push(p.to_string());
+                layout.push(todo_txt_model::DescriptionToken::Project(p.to_string()));
             }
             Token::Context(c) => {
                 tracing::debug!("token context: {}, contexts: {:?}", c, contexts);
                 contexts.push(c.to_string());
+                layout.push(todo_txt_model::DescriptionToken::Context(c.to_string()));
+            }
+            Token::Tag(k, v) if tags.contains_key(k) => {
+                // a repeated key is text, the first value is the tag
+                tracing::debug!("token repeated tag: {}:{}, tags: {:?}", k, v, tags);
+                if !description.is_empty() {
+                    description.push(crate::TOKEN_SEPARATOR);
+                }
+                let word = format!("{}:{}", k, v);
+                description.push_str(&word);
+                layout.push(todo_txt_model::DescriptionToken::Word(word));
+            }
+            Token::Tag(k, v) => {
+                tracing::debug!("token tag: {}:{}, tags: {:?}", k, v, tags);
+                tags.insert(k, v);
+                layout.push(todo_txt_model::DescriptionToken::Tag(
+                    k.to_string(),
+                    v.to_string(),
+                ));
             }
             Token::Description(d) => {
                 if !description.is_empty() {
@@ -64,8 +94,9 @@ pub fn from_str(value: &str) -> Result<todo_txt_model::Task> {
                 }
                 tracing::debug!("token description: {}, description: {}", d, description);
                 description.push_str(d);
+                layout.push(todo_txt_model::DescriptionToken::Word(d.to_string()));
             }
-            _ => return Err(TodoTxtRsError::Syntax),
+            _ => return Err(syntax_error(span, SyntaxReason::UnexpectedToken)),
         }
     }
     let projects = distinct_vec_hold_order(projects);
@@ -73,9 +104,10 @@ pub fn from_str(value: &str) -> Result<todo_txt_model::Task> {
     tracing::debug!("description: {:?}", description);
     tracing::debug!("projects: {:?}", projects);
     tracing::debug!("contexts: {:?}", contexts);
+    tracing::debug!("tags: {:?}", tags);
 
     if description.is_empty() {
-        return Err(TodoTxtRsError::Syntax);
+        return Err(syntax_error(end..end, SyntaxReason::EmptyDescription));
     }
 
     Ok(todo_txt_model::Task {
@@ -95,6 +127,8 @@ pub fn from_str(value: &str) -> Result<todo_txt_model::Task> {
             value: description,
             project: projects,
             context: contexts,
+            tags,
+            tokens: layout,
         },
     })
 }
@@ -118,6 +152,7 @@ enum Token<'a> {
     Description(&'a str),
     Project(&'a str),
     Context(&'a str),
+    Tag(&'a str, &'a str),
 }
 
 impl<'a> From<&'a str> for Token<'a> {
@@ -126,7 +161,8 @@ impl<'a> From<&'a str> for Token<'a> {
             t if t == crate::COMPLETE_MARKER.to_string() => Self::Done,
             t if t.starts_with(crate::PRIORITY_MARKER_PRE)
                 && t.ends_with(crate::PRIORITY_MARKER_POST)
-                && t.len() == 3 =>
+                && t.len() == 3
+                && t.as_bytes()[1].is_ascii_uppercase() =>
             {
                 Self::Priority(
                     t.trim_start_matches(crate::PRIORITY_MARKER_PRE)
@@ -142,28 +178,53 @@ impl<'a> From<&'a str> for Token<'a> {
             t if t.starts_with(crate::CONTEXT_MARKER) => {
                 Self::Context(t.trim_start_matches(crate::CONTEXT_MARKER))
             }
+            t if split_tag(t).is_some() => {
+                let (k, v) = split_tag(t).unwrap();
+                Self::Tag(k, v)
+            }
             t => Self::Description(t),
         }
     }
 }
 
+/// Split a `key:value` token.
+/// note: key and value must not be empty and the value must not contain another separator
+/// note: values starting with `//` are treated as URLs, not tags
+fn split_tag(value: &str) -> Option<(&str, &str)> {
+    let (key, value) = value.split_once(crate::TAG_SEPARATOR)?;
+    if key.is_empty()
+        || value.is_empty()
+        || value.contains(crate::TAG_SEPARATOR)
+        || value.starts_with("//")
+    {
+        return None;
+    }
+    Some((key, value))
+}
+
+fn syntax_error(span: std::ops::Range<usize>, reason: SyntaxReason) -> TodoTxtRsError {
+    SyntaxError::new(span, reason).into()
+}
+
+type Spanned<'a> = (std::ops::Range<usize>, Token<'a>);
+
 #[tracing::instrument(parent = None, skip(tokens))]
 fn get_task_state<'a, IT>(
     mut tokens: std::iter::Peekable<IT>,
-) -> Result<(todo_txt_model::TaskState, std::iter::Peekable<IT>)>
+) -> (todo_txt_model::TaskState, std::iter::Peekable<IT>)
 where
-    IT: Iterator<Item = Token<'a>>,
+    IT: Iterator<Item = Spanned<'a>>,
 {
-    let token = tokens.peek().ok_or(TodoTxtRsError::Syntax)?;
+    let token = tokens.peek();
     tracing::debug!("token {:?}", token);
     let state = match token {
-        Token::Done => {
+        Some((_, Token::Done)) => {
             tokens.next();
             todo_txt_model::TaskState::Done
         }
         _ => todo_txt_model::TaskState::Todo,
     };
-    Ok((state, tokens))
+    (state, tokens)
 }
 
 #[tracing::instrument(parent = None, skip(tokens))]
@@ -174,18 +235,15 @@ fn get_task_priority<'a, IT>(
     std::iter::Peekable<IT>,
 )>
 where
-    IT: Iterator<Item = Token<'a>>,
+    IT: Iterator<Item = Spanned<'a>>,
 {
-    let token = tokens.peek().ok_or(TodoTxtRsError::Syntax)?;
+    let token = tokens.peek();
     tracing::debug!("token {:?}", token);
-    let priority = match *token {
-        Token::Priority(p) => {
-            if let Some(p) = p.chars().next() {
-                tokens.next();
-                Some(todo_txt_model::TaskPriority::from(p))
-            } else {
-                return Err(TodoTxtRsError::Syntax);
-            }
+    let priority = match token {
+        Some((_, Token::Priority(p))) => {
+            let priority = p.chars().next().map(todo_txt_model::TaskPriority::from);
+            tokens.next();
+            priority
         }
         _ => None,
     };
@@ -197,12 +255,13 @@ fn get_date<'a, IT>(
     mut tokens: std::iter::Peekable<IT>,
 ) -> Result<(Option<chrono::NaiveDate>, std::iter::Peekable<IT>)>
 where
-    IT: Iterator<Item = Token<'a>>,
+    IT: Iterator<Item = Spanned<'a>>,
 {
-    let token = tokens.peek().ok_or(TodoTxtRsError::Syntax)?;
+    let token = tokens.peek();
     tracing::debug!("token {:?}", token);
-    let date = match *token {
-        Token::Date(d) => {
+    let date = match token {
+        Some((_, Token::Date(d))) => {
+            let d = *d;
             tokens.next();
             Some(d)
         }
@@ -232,6 +291,8 @@ mod test {
                     value: "123 abc description".to_string(),
                     project: vec!["project".to_string(), "project2".to_string()],
                     context: vec!["context".to_string(), "ctx2".to_string()],
+                    tags: Default::default(),
+                    tokens: Vec::new(),
                 }
             }
         )
@@ -253,6 +314,8 @@ mod test {
                     value: "description".to_string(),
                     project: Vec::new(),
                     context: Vec::new(),
+                    tags: Default::default(),
+                    tokens: Vec::new(),
                 }
             }
         );
@@ -270,8 +333,68 @@ mod test {
                     value: "description".to_string(),
                     project: Vec::new(),
                     context: Vec::new(),
+                    tags: Default::default(),
+                    tokens: Vec::new(),
                 }
             }
         )
     }
+
+    #[test]
+    fn test_from_str_tags() {
+        let task =
+            super::from_str("call mom due:2020-01-10 t:2020-01-05 see http://example.com note:a:b");
+        assert!(task.is_ok());
+        let task = task.unwrap();
+        assert_eq!(
+            task.description.value,
+            "call mom see http://example.com note:a:b"
+        );
+        assert_eq!(
+            task.description.tags,
+            [("due", "2020-01-10"), ("t", "2020-01-05")]
+                .into_iter()
+                .collect()
+        );
+        assert!(super::from_str("due:2020-01-10").is_err());
+
+        let line = "pay due:2020-01-10 rent due:2020-01-11";
+        let task = super::from_str(line).unwrap();
+        assert_eq!(task.description.tags.get("due"), Some("2020-01-10"));
+        assert_eq!(task.description.value, "pay rent due:2020-01-11");
+        assert_eq!(crate::to_string(&task), line);
+    }
+
+    #[test]
+    fn test_from_str_errors() {
+        use todo_txt_model::{prelude::TodoTxtRsError, SyntaxReason};
+        let error = |value: &str| match super::from_str(value) {
+            Err(TodoTxtRsError::Syntax(e)) => (e.span, e.reason),
+            r => panic!("unexpected result: {:?}", r),
+        };
+        assert_eq!(error("  "), (2..2, SyntaxReason::EmptyLine));
+        assert_eq!(
+            error("x (A) +home"),
+            (11..11, SyntaxReason::EmptyDescription)
+        );
+        assert_eq!(
+            error("call mom 2020-01-01"),
+            (9..19, SyntaxReason::UnexpectedToken)
+        );
+    }
+
+    #[test]
+    fn test_from_str_priority_and_date_like_text() {
+        for (line, value) in [
+            ("(1) call mom", "(1) call mom"),
+            ("(a) call mom", "(a) call mom"),
+            ("2020-13-01 call mom", "2020-13-01 call mom"),
+            ("x (B) 2020-01-01 2020-02-30 call", "2020-02-30 call"),
+        ] {
+            let task = super::from_str(line).unwrap();
+            assert_eq!(task.description.value, value);
+            assert_eq!(crate::to_string(&task), line);
+        }
+        assert_eq!(super::from_str("(1) call mom").unwrap().priority, None);
+    }
 }
@@ -14,3 +14,4 @@ pub const PRIORITY_MARKER_PRE: char = '(';
 pub const PRIORITY_MARKER_POST: char = ')';
 pub const PROJECT_MARKER: char = '+';
 pub const CONTEXT_MARKER: char = '@';
+pub const TAG_SEPARATOR: char = ':';
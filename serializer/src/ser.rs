@@ -16,10 +16,12 @@
 ///                        value: "123 abc description".to_string(),
 ///                        project: vec!["project".to_string(), "project2".to_string()],
 ///                        context: vec!["context".to_string(), "ctx2".to_string()],
+///                        tags: [("due", "2020-01-10")].into_iter().collect(),
+///                        tokens: Vec::new(),
 ///                    }
 ///                };
 /// let result = todo_txt_serializer::to_string(&task);
-/// let expected = "x (A) 2020-01-01 2020-01-02 123 abc description +project +project2 @context @ctx2";
+/// let expected = "x (A) 2020-01-01 2020-01-02 123 abc description +project +project2 @context @ctx2 due:2020-01-10";
 /// assert_eq!(result, expected);
 #[tracing::instrument(parent = None)]
 pub fn to_string(value: &todo_txt_model::Task) -> String {
@@ -75,12 +77,132 @@ fn write_task_description(
     description: &todo_txt_model::TaskDescription,
 ) -> String {
     tracing::debug!("description: {:?}", description.value);
+    if !description.tokens.is_empty() {
+        return write_task_layout(out, description);
+    }
     if !out.is_empty() {
         out.push(crate::TOKEN_SEPARATOR);
     }
     out.push_str(&description.value);
     let out = write_task_project(out, &description.project);
-    write_task_context(out, &description.context)
+    let out = write_task_context(out, &description.context);
+    write_task_tags(out, &description.tags)
+}
+
+/// Write the task description following the order of its parsed tokens.
+/// note: unchanged tokens are written back as they were read
+/// note: a value that was extended keeps its words and the extra text goes to the end
+/// note: a value that was replaced is written where its first word was
+/// note: a tag is written once, where its key first was, with its current value
+/// note: removed projects, contexts and tags are dropped, new ones go to the end
+#[tracing::instrument(parent = None, skip(description))]
+fn write_task_layout(mut out: String, description: &todo_txt_model::TaskDescription) -> String {
+    use todo_txt_model::DescriptionToken;
+
+    let mut layout_value = String::new();
+    let mut layout_tags = todo_txt_model::TaskTags::new();
+    for token in &description.tokens {
+        match token {
+            DescriptionToken::Word(w) => {
+                if !layout_value.is_empty() {
+                    layout_value.push(crate::TOKEN_SEPARATOR);
+                }
+                layout_value.push_str(w);
+            }
+            DescriptionToken::Tag(k, v) => {
+                layout_tags.insert(k.as_str(), v.as_str());
+            }
+            _ => {}
+        }
+    }
+    let value_kept = layout_value == description.value;
+    let value_suffix = description
+        .value
+        .strip_prefix(layout_value.as_str())
+        .filter(|_| !value_kept && !layout_value.is_empty())
+        .and_then(|rest| rest.strip_prefix(crate::TOKEN_SEPARATOR));
+    tracing::debug!("value kept: {}, suffix: {:?}", value_kept, value_suffix);
+
+    let mut value_written = value_kept || value_suffix.is_some();
+    let mut written_tags = Vec::new();
+    for token in &description.tokens {
+        match token {
+            DescriptionToken::Word(w) => {
+                if value_kept || value_suffix.is_some() {
+                    out = push_token(out, w);
+                } else if !value_written {
+                    if !description.value.is_empty() {
+                        out = push_token(out, &description.value);
+                    }
+                    value_written = true;
+                }
+            }
+            DescriptionToken::Project(p) if description.project.contains(p) => {
+                out = push_marked_token(out, crate::PROJECT_MARKER, p);
+            }
+            DescriptionToken::Context(c) if description.context.contains(c) => {
+                out = push_marked_token(out, crate::CONTEXT_MARKER, c);
+            }
+            DescriptionToken::Tag(k, _) => match description.tags.get(k) {
+                Some(current) if !written_tags.contains(&k.as_str()) => {
+                    out = push_tag(out, k, current);
+                    written_tags.push(k.as_str());
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    if let Some(suffix) = value_suffix {
+        out = push_token(out, suffix);
+    } else if !value_written && !description.value.is_empty() {
+        out = push_token(out, &description.value);
+    }
+    let is_new = |token: DescriptionToken| !description.tokens.contains(&token);
+    for p in &description.project {
+        if is_new(DescriptionToken::Project(p.clone())) {
+            out = push_marked_token(out, crate::PROJECT_MARKER, p);
+        }
+    }
+    for c in &description.context {
+        if is_new(DescriptionToken::Context(c.clone())) {
+            out = push_marked_token(out, crate::CONTEXT_MARKER, c);
+        }
+    }
+    for (k, v) in description.tags.iter() {
+        if !layout_tags.contains_key(k) {
+            out = push_tag(out, k, v);
+        }
+    }
+    out
+}
+
+fn push_token(mut out: String, token: &str) -> String {
+    if !out.is_empty() {
+        out.push(crate::TOKEN_SEPARATOR);
+    }
+    out.push_str(token);
+    out
+}
+
+fn push_marked_token(mut out: String, marker: char, token: &str) -> String {
+    if !out.is_empty() {
+        out.push(crate::TOKEN_SEPARATOR);
+    }
+    out.push(marker);
+    out.push_str(token);
+    out
+}
+
+fn push_tag(mut out: String, key: &str, value: &str) -> String {
+    if !out.is_empty() {
+        out.push(crate::TOKEN_SEPARATOR);
+    }
+    out.push_str(key);
+    out.push(crate::TAG_SEPARATOR);
+    out.push_str(value);
+    out
 }
 
 /// Write the task project to the output string.
@@ -111,6 +233,21 @@ fn write_task_context(mut out: String, context: &[String]) -> String {
     out
 }
 
+/// Write the task tags to the output string.
+#[tracing::instrument(parent = None, skip(tags))]
+fn write_task_tags(mut out: String, tags: &todo_txt_model::TaskTags) -> String {
+    for (key, value) in tags.iter() {
+        tracing::debug!("tag: {:?}:{:?}", key, value);
+        if !out.is_empty() {
+            out.push(crate::TOKEN_SEPARATOR);
+        }
+        out.push_str(key);
+        out.push(crate::TAG_SEPARATOR);
+        out.push_str(value);
+    }
+    out
+}
+
 #[cfg(test)]
 mod test {
     #[test]
@@ -161,12 +298,24 @@ mod test {
         assert_eq!(result, "");
     }
 
+    #[test]
+    fn test_write_task_tags() {
+        let tags = [("due", "2021-01-01"), ("rec", "1w")].into_iter().collect();
+        let result = super::write_task_tags(String::new(), &tags);
+        assert_eq!(result, "due:2021-01-01 rec:1w");
+        let tags = todo_txt_model::TaskTags::new();
+        let result = super::write_task_tags(String::new(), &tags);
+        assert_eq!(result, "");
+    }
+
     #[test]
     fn test_write_task_description() {
         let description = todo_txt_model::TaskDescription {
             value: "description".to_string(),
             project: vec!["project".to_string()],
             context: vec!["context".to_string()],
+            tags: Default::default(),
+            tokens: Vec::new(),
         };
         let result = super::write_task_description(String::new(), &description);
         assert_eq!(result, "description +project @context");
@@ -175,11 +324,26 @@ mod test {
             value: "description".to_string(),
             project: vec![],
             context: vec![],
+            tags: Default::default(),
+            tokens: Vec::new(),
         };
         let result = super::write_task_description(String::new(), &description);
         assert_eq!(result, "description");
     }
 
+    #[test]
+    fn test_write_task_layout() {
+        let task = crate::from_str("call +acme about  @phone invoice rec:1w").unwrap();
+        let result = super::write_task_layout(String::new(), &task.description);
+        assert_eq!(result, "call +acme about  @phone invoice rec:1w");
+
+        let mut description = task.description;
+        description.project.clear();
+        description.tags.insert("due", "2021-01-01");
+        let result = super::write_task_layout(String::new(), &description);
+        assert_eq!(result, "call about  @phone invoice rec:1w due:2021-01-01");
+    }
+
     #[test]
     fn test_to_string() {
         let task = todo_txt_model::Task {
@@ -191,6 +355,8 @@ mod test {
                 value: "description".to_string(),
                 project: vec!["project".to_string()],
                 context: vec!["context".to_string()],
+                tags: Default::default(),
+                tokens: Vec::new(),
             },
         };
         let result = super::to_string(&task);
@@ -204,6 +370,8 @@ mod test {
                 value: "description".to_string(),
                 project: vec![],
                 context: vec![],
+                tags: Default::default(),
+                tokens: Vec::new(),
             },
         };
         let result = super::to_string(&task);
@@ -23,8 +23,28 @@ fn test_from_str_fully() {
     let str = to_string(&task);
     assert_eq!(
         str,
-        "x (A) 2021-01-01 2021-01-02 task hello abc +proj1 +project @context @ctx2"
+        "x (A) 2021-01-01 2021-01-02 +proj1 task +project hello @context abc @ctx2"
+    );
+}
+
+#[test]
+fn test_from_str_edited() {
+    let task = from_str("call +acme about @phone invoice due:2021-01-05 +acme");
+    assert!(task.is_ok());
+    let mut task = task.unwrap();
+    task.state = todo_txt_model::TaskState::Done;
+    task.description.tags.insert("due", "2021-01-06");
+    task.description.context.clear();
+    task.description.project.push("ops".to_string());
+    task.description.value.push_str(" today");
+    let str = to_string(&task);
+    assert_eq!(
+        str,
+        "x call +acme about invoice due:2021-01-06 +acme today +ops"
     );
+    task.description.value = "ring".to_string();
+    let str = to_string(&task);
+    assert_eq!(str, "x ring +acme due:2021-01-06 +acme +ops");
 }
 
 #[test]
@@ -38,6 +58,8 @@ fn test_to_string() {
             value: "task hello abc".to_string(),
             project: vec!["proj1".to_string(), "project".to_string()],
             context: vec!["context".to_string(), "ctx2".to_string()],
+            tags: Default::default(),
+            tokens: Vec::new(),
         },
     };
     let str = to_string(&task);
@@ -50,3 +72,17 @@ fn test_to_string() {
     let expected = expected.unwrap();
     assert_eq!(expected, task);
 }
+
+#[test]
+fn test_tags_round_trip() {
+    let task = from_str("(B) pay rent due:2021-02-01 +home rec:+1m");
+    assert!(task.is_ok());
+    let task = task.unwrap();
+    assert_eq!(task.description.tags.get("due"), Some("2021-02-01"));
+    assert_eq!(task.description.tags.get("rec"), Some("+1m"));
+    let str = to_string(&task);
+    assert_eq!(str, "(B) pay rent due:2021-02-01 +home rec:+1m");
+    let expected = from_str(&str);
+    assert!(expected.is_ok());
+    assert_eq!(expected.unwrap(), task);
+}